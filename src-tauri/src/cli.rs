@@ -0,0 +1,230 @@
+use crate::ipc::{TypstDiagnosticSeverity, TypstSourceDiagnostic};
+use crate::lsp;
+use crate::project::{compile, Project};
+use env_logger::Env;
+use std::fs;
+use std::path::PathBuf;
+use std::str::FromStr;
+use typst::foundations::Smart;
+use typst::syntax::package::PackageSpec;
+use typst::syntax::{FileId, VirtualPath};
+use typst::World;
+
+const USAGE: &str = "usage: typstudio compile <project-dir> [-o <output.pdf>] [--format human|json]
+       typstudio lsp";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum DiagnosticFormat {
+    Human,
+    Json,
+}
+
+struct CompileArgs {
+    root: PathBuf,
+    output: Option<PathBuf>,
+    format: DiagnosticFormat,
+}
+
+/// Runs the command-line interface if the arguments request a subcommand.
+/// Returns the exit code, or [Option::None] if the editor should be launched.
+pub fn run(args: &[String]) -> Option<i32> {
+    let command: fn(&[String]) -> i32 = match args.first().map(String::as_str) {
+        Some("compile") => |args| match parse_compile_args(args) {
+            Ok(args) => run_compile(args),
+            Err(e) => {
+                eprintln!("error: {}\n{}", e, USAGE);
+                2
+            }
+        },
+        // The project is determined by the client upon initialization
+        Some("lsp") => |_| lsp::run(),
+        _ => return None,
+    };
+
+    attach_console();
+    // Only warnings are logged, as log lines would mix with the diagnostics
+    env_logger::init_from_env(Env::default().default_filter_or("warn"));
+    Some(command(&args[1..]))
+}
+
+/// Release builds on Windows have no console, so the output of subcommands
+/// which are run from a terminal would be lost. Attaches to the console of
+/// the parent process, unless the output is redirected, e.g. to a language
+/// client.
+#[cfg(windows)]
+fn attach_console() {
+    const ATTACH_PARENT_PROCESS: u32 = u32::MAX;
+    const STD_ERROR_HANDLE: u32 = -12i32 as u32;
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn AttachConsole(process_id: u32) -> i32;
+        fn GetStdHandle(std_handle: u32) -> *mut std::ffi::c_void;
+    }
+
+    // SAFETY: Both functions only take plain integer arguments
+    unsafe {
+        if GetStdHandle(STD_ERROR_HANDLE).is_null() {
+            AttachConsole(ATTACH_PARENT_PROCESS);
+        }
+    }
+}
+
+#[cfg(not(windows))]
+fn attach_console() {}
+
+fn parse_compile_args(args: &[String]) -> Result<CompileArgs, String> {
+    let mut root = None;
+    let mut output = None;
+    let mut format = DiagnosticFormat::Human;
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "-o" | "--output" => {
+                let value = iter.next().ok_or("missing value for --output")?;
+                output = Some(PathBuf::from(value));
+            }
+            "--format" => {
+                format = match iter.next().map(String::as_str) {
+                    Some("human") => DiagnosticFormat::Human,
+                    Some("json") => DiagnosticFormat::Json,
+                    _ => return Err("expected `human` or `json` for --format".into()),
+                };
+            }
+            _ if root.is_none() && !arg.starts_with('-') => root = Some(PathBuf::from(arg)),
+            _ => return Err(format!("unexpected argument `{}`", arg)),
+        }
+    }
+
+    Ok(CompileArgs {
+        root: root.ok_or("missing project directory")?,
+        output,
+        format,
+    })
+}
+
+fn run_compile(args: CompileArgs) -> i32 {
+    let project = Project::load_from_path(args.root);
+    let main = project.config.read().unwrap().main.clone();
+    let main = match main {
+        Some(main) => main,
+        None => {
+            eprintln!("error: the main source of {:?} is not set", project.root);
+            return 1;
+        }
+    };
+    if VirtualPath::new(&main)
+        .resolve(&project.root)
+        .map_or(true, |path| !path.is_file())
+    {
+        eprintln!("error: the main source {:?} does not exist", main);
+        return 1;
+    }
+
+    let mut world = project.world.lock().unwrap();
+    let output = match compile(&project, &mut world, 0) {
+        Some(output) => output,
+        None => {
+            eprintln!("error: the main source of {:?} is not set", project.root);
+            return 1;
+        }
+    };
+
+    let diagnostics = output.event.diagnostics.unwrap_or_default();
+    let detached_diagnostics = output.event.detached_diagnostics.unwrap_or_default();
+
+    match args.format {
+        DiagnosticFormat::Human => {
+            for diagnostic in &diagnostics {
+                print_source_diagnostic(&*world, diagnostic);
+            }
+            for diagnostic in &detached_diagnostics {
+                eprintln!(
+                    "{}: {}",
+                    severity_label(&diagnostic.severity),
+                    diagnostic.message
+                );
+                for hint in &diagnostic.hints {
+                    eprintln!("  = hint: {}", hint);
+                }
+            }
+        }
+        DiagnosticFormat::Json => {
+            let json = serde_json::json!({
+                "diagnostics": diagnostics,
+                "detached_diagnostics": detached_diagnostics,
+            });
+            println!("{}", json);
+        }
+    }
+
+    let doc = match output.document {
+        Some(doc) => doc,
+        None => return 1,
+    };
+
+    // Defaults to the path of the main source, with the PDF extension
+    let path = args.output.unwrap_or_else(|| {
+        VirtualPath::new(main.with_extension("pdf"))
+            .resolve(&project.root)
+            .unwrap_or_else(|| project.root.join("main.pdf"))
+    });
+    let pdf = typst_pdf::pdf(&doc, Smart::Auto, None);
+    if let Err(e) = fs::write(&path, pdf) {
+        eprintln!("error: unable to write {:?}: {}", path, e);
+        return 1;
+    }
+
+    0
+}
+
+fn severity_label(severity: &TypstDiagnosticSeverity) -> &'static str {
+    match severity {
+        TypstDiagnosticSeverity::Error => "error",
+        TypstDiagnosticSeverity::Warning => "warning",
+    }
+}
+
+/// Prints a diagnostic with its location in the `path:line:column` form.
+fn print_source_diagnostic<W: World>(world: &W, diagnostic: &TypstSourceDiagnostic) {
+    let package = diagnostic
+        .package
+        .as_ref()
+        .and_then(|spec| PackageSpec::from_str(spec).ok());
+    let id = FileId::new(package, VirtualPath::new(&diagnostic.path));
+
+    let position = world.source(id).ok().and_then(|source| {
+        let byte = source
+            .utf16_to_byte(diagnostic.range.start)
+            .unwrap_or(source.len_bytes());
+        Some((
+            source.byte_to_line(byte)? + 1,
+            source.byte_to_column(byte)? + 1,
+        ))
+    });
+
+    let location = match &diagnostic.package {
+        Some(spec) => format!("{}{}", spec, diagnostic.path.display()),
+        None => diagnostic.path.display().to_string(),
+    };
+    match position {
+        Some((line, column)) => eprintln!(
+            "{}: {}:{}:{}: {}",
+            severity_label(&diagnostic.severity),
+            location,
+            line,
+            column,
+            diagnostic.message
+        ),
+        None => eprintln!(
+            "{}: {}: {}",
+            severity_label(&diagnostic.severity),
+            location,
+            diagnostic.message
+        ),
+    }
+    for hint in &diagnostic.hints {
+        eprintln!("  = hint: {}", hint);
+    }
+}
@@ -0,0 +1,145 @@
+use crate::ipc::{TypstPngExportOptions, TypstSvgExportOptions};
+use crate::project::Project;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use typst::layout::Page;
+use typst::model::Document;
+use typst::visualize::Color;
+
+/// The highest resolution of PNG exports. A page of A4 is about 20,000 pixels
+/// wide at this resolution already.
+const MAX_DPI: f32 = 2400.0;
+
+#[derive(thiserror::Error, Debug)]
+pub enum ExportError {
+    #[error("there is no document to export")]
+    NoDocument,
+    #[error("the document has no pages {0} to {1}")]
+    InvalidPages(usize, usize),
+    #[error("the resolution must be above 0 and at most 2400 dpi, but is {0}")]
+    InvalidDpi(f32),
+    #[error("unable to encode page {0}")]
+    Encode(usize),
+    #[error("io error occurred")]
+    IO(#[from] io::Error),
+}
+
+/// Renders the pages of the project's cached document to PNG files in the
+/// directory, named after the pattern of the options. Returns the paths of
+/// the written files.
+pub fn export_png(
+    project: &Project,
+    directory: &Path,
+    options: &TypstPngExportOptions,
+) -> Result<Vec<PathBuf>, ExportError> {
+    let dpi = options.dpi;
+    if !dpi.is_finite() || dpi <= 0.0 || dpi > MAX_DPI {
+        return Err(ExportError::InvalidDpi(dpi));
+    }
+    export_pages(
+        project,
+        directory,
+        options.first_page,
+        options.last_page,
+        &options.pattern,
+        "png",
+        |number, page| {
+            let pixmap = typst_render::render(&page.frame, dpi / 72.0, Color::WHITE);
+            pixmap.encode_png().map_err(|_| ExportError::Encode(number))
+        },
+    )
+}
+
+/// Converts the pages of the project's cached document to SVG files in the
+/// directory, named after the pattern of the options. Returns the paths of
+/// the written files.
+///
+/// Text is always converted to outlines, as typst-svg cannot embed fonts. The
+/// files display the same without the fonts, but the text cannot be selected.
+pub fn export_svg(
+    project: &Project,
+    directory: &Path,
+    options: &TypstSvgExportOptions,
+) -> Result<Vec<PathBuf>, ExportError> {
+    export_pages(
+        project,
+        directory,
+        options.page,
+        options.page,
+        &options.pattern,
+        "svg",
+        |_, page| Ok(typst_svg::svg(&page.frame).into_bytes()),
+    )
+}
+
+/// Writes each page in the inclusive range of one-based page numbers, which
+/// defaults to all pages, to its own file.
+fn export_pages(
+    project: &Project,
+    directory: &Path,
+    first: Option<usize>,
+    last: Option<usize>,
+    pattern: &str,
+    extension: &str,
+    encode: impl Fn(usize, &Page) -> Result<Vec<u8>, ExportError>,
+) -> Result<Vec<PathBuf>, ExportError> {
+    // The cache is not locked while rendering, which would block compilations
+    let document = project.cache.read().unwrap().document.clone();
+    let document = document.ok_or(ExportError::NoDocument)?;
+    let pages = page_range(&document, first, last)?;
+    let pattern = file_pattern(pattern, extension, pages.len() > 1);
+
+    let name = export_name(project);
+    let width = document.pages.len().to_string().len();
+    let mut paths = vec![];
+    for number in pages {
+        let data = encode(number, &document.pages[number - 1])?;
+
+        let file_name = pattern
+            .replace("{name}", &name)
+            .replace("{page}", &format!("{:0width$}", number, width = width));
+        let path = directory.join(file_name);
+        fs::write(&path, data)?;
+        paths.push(path);
+    }
+    Ok(paths)
+}
+
+fn page_range(
+    document: &Document,
+    first: Option<usize>,
+    last: Option<usize>,
+) -> Result<Vec<usize>, ExportError> {
+    let count = document.pages.len();
+    let first = first.unwrap_or(1);
+    let last = last.unwrap_or(count);
+    if first == 0 || first > last || last > count {
+        return Err(ExportError::InvalidPages(first, last));
+    }
+    Ok((first..=last).collect())
+}
+
+/// Ensures that the pattern ends with the extension and, if several pages
+/// are exported, contains the page number. Files would overwrite each other
+/// otherwise.
+fn file_pattern(pattern: &str, extension: &str, several: bool) -> String {
+    let suffix = format!(".{}", extension);
+    let stem = pattern.strip_suffix(&suffix).unwrap_or(pattern);
+    match several && !stem.contains("{page}") {
+        true => format!("{}-{{page}}{}", stem, suffix),
+        false => format!("{}{}", stem, suffix),
+    }
+}
+
+/// The name of exported files, which is the name of the main source.
+fn export_name(project: &Project) -> String {
+    let config = project.config.read().unwrap();
+    config
+        .main
+        .as_ref()
+        .and_then(|main| main.file_stem())
+        .map_or("export".to_string(), |stem| {
+            stem.to_string_lossy().to_string()
+        })
+}
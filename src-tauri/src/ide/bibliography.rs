@@ -0,0 +1,284 @@
+use crate::ipc::TypstBibliographyEntry;
+use crate::project::ProjectWorld;
+use hayagriva::{Entry, Library, Person};
+use log::warn;
+use once_cell::sync::Lazy;
+use siphasher::sip128::{Hasher128, SipHasher};
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use typst::model::Document;
+use typst::syntax::{ast, FileId, LinkedNode, Source, SyntaxKind};
+use typst::World;
+use typst_ide::{Completion, CompletionKind};
+
+/// Results by file, with the hash of the content they were derived from.
+type FileCache<T> = Lazy<Mutex<HashMap<FileId, (u128, T)>>>;
+
+/// The bibliography files referenced by each source.
+static SOURCE_FILES: FileCache<Arc<Vec<FileId>>> = Lazy::new(Default::default);
+
+/// The entries of each bibliography file.
+static FILE_ENTRIES: FileCache<Arc<Vec<TypstBibliographyEntry>>> = Lazy::new(Default::default);
+
+/// Returns the cached result for the file if its content has not changed, or
+/// computes and caches it otherwise.
+fn cached<T: Clone>(
+    cache: &FileCache<T>,
+    id: FileId,
+    content: &impl Hash,
+    compute: impl FnOnce() -> T,
+) -> T {
+    let mut hasher = SipHasher::new();
+    content.hash(&mut hasher);
+    let hash = hasher.finish128().as_u128();
+
+    if let Some((cached_hash, value)) = cache.lock().unwrap().get(&id) {
+        if *cached_hash == hash {
+            return value.clone();
+        }
+    }
+    let value = compute();
+    cache.lock().unwrap().insert(id, (hash, value.clone()));
+    value
+}
+
+/// Finds the bibliography files passed to `bibliography(...)` in the sources
+/// of the project.
+pub fn bibliography_files(world: &ProjectWorld) -> Vec<FileId> {
+    let mut files = vec![];
+    for id in world.source_ids() {
+        let Ok(source) = world.source(id) else {
+            continue;
+        };
+        let paths = cached(&SOURCE_FILES, id, &source, || {
+            let mut paths = vec![];
+            collect_paths(id, &LinkedNode::new(source.root()), &mut paths);
+            Arc::new(paths)
+        });
+        for file in paths.iter() {
+            if !files.contains(file) {
+                files.push(*file);
+            }
+        }
+    }
+    files
+}
+
+fn collect_paths(id: FileId, node: &LinkedNode, files: &mut Vec<FileId>) {
+    if let Some(call) = node.cast::<ast::FuncCall>() {
+        let is_bibliography =
+            matches!(call.callee(), ast::Expr::Ident(ident) if ident.as_str() == "bibliography");
+        let first = call.args().items().find_map(|arg| match arg {
+            ast::Arg::Pos(expr) => Some(expr),
+            _ => None,
+        });
+        if is_bibliography {
+            let paths = match first {
+                Some(ast::Expr::Str(path)) => vec![path.get()],
+                Some(ast::Expr::Array(array)) => array
+                    .items()
+                    .filter_map(|item| match item {
+                        ast::ArrayItem::Pos(ast::Expr::Str(path)) => Some(path.get()),
+                        _ => None,
+                    })
+                    .collect(),
+                _ => vec![],
+            };
+            for path in paths {
+                let file = id.join(&path);
+                if !files.contains(&file) {
+                    files.push(file);
+                }
+            }
+        }
+    }
+
+    for child in node.children() {
+        collect_paths(id, &child, files);
+    }
+}
+
+/// Parses the bibliography files of the project. Files which cannot be read
+/// or parsed are skipped. Files are only parsed again once they change.
+pub fn bibliography(world: &ProjectWorld) -> Vec<TypstBibliographyEntry> {
+    let mut entries = vec![];
+    for id in bibliography_files(world) {
+        let path = id.vpath().as_rooted_path();
+        let bytes = match world.file(id) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                warn!("unable to read bibliography {:?}: {:?}", path, e);
+                continue;
+            }
+        };
+        let file_entries = cached(&FILE_ENTRIES, id, &bytes, || {
+            let library = parse_library(path, &bytes);
+            let file_entries = library.iter().flat_map(|library| library.iter());
+            Arc::new(file_entries.map(|entry| map_entry(entry, path)).collect())
+        });
+        entries.extend(file_entries.iter().cloned());
+    }
+    entries
+}
+
+/// Finds the entry for `key` in the bibliography files of the project.
+pub fn bibliography_entry(world: &ProjectWorld, key: &str) -> Option<TypstBibliographyEntry> {
+    bibliography(world)
+        .into_iter()
+        .find(|entry| entry.key == key)
+}
+
+fn parse_library(path: &Path, bytes: &[u8]) -> Option<Library> {
+    let src = std::str::from_utf8(bytes).ok()?;
+    let ext = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or_default();
+
+    match ext.to_lowercase().as_str() {
+        "yml" | "yaml" => hayagriva::io::from_yaml_str(src)
+            .map_err(|e| warn!("unable to parse bibliography {:?}: {}", path, e))
+            .ok(),
+        "bib" => hayagriva::io::from_biblatex_str(src)
+            .map_err(|errors| {
+                warn!(
+                    "unable to parse bibliography {:?} ({} errors)",
+                    path,
+                    errors.len()
+                )
+            })
+            .ok(),
+        _ => None,
+    }
+}
+
+fn map_entry(entry: &Entry, path: &Path) -> TypstBibliographyEntry {
+    TypstBibliographyEntry {
+        key: entry.key().to_string(),
+        title: entry.title().map(|title| title.value.to_str().to_string()),
+        authors: entry
+            .authors()
+            .unwrap_or_default()
+            .iter()
+            .map(person_name)
+            .collect(),
+        year: entry.date().map(|date| date.year),
+        container: entry
+            .parents()
+            .first()
+            .and_then(|parent| parent.title())
+            .map(|title| title.value.to_str().to_string()),
+        path: path.to_path_buf(),
+    }
+}
+
+fn person_name(person: &Person) -> String {
+    [
+        person.given_name.as_deref(),
+        person.prefix.as_deref(),
+        Some(person.name.as_str()),
+        person.suffix.as_deref(),
+    ]
+    .into_iter()
+    .flatten()
+    .collect::<Vec<_>>()
+    .join(" ")
+}
+
+/// Completes at the cursor with typst-ide, adding the bibliography keys of
+/// the project after `@` and within `cite(<...>)`. Keys are read from the
+/// bibliography files, as the document may not be compiled or up-to-date.
+pub fn autocomplete(
+    world: &ProjectWorld,
+    document: Option<&Document>,
+    source: &Source,
+    cursor: usize,
+    explicit: bool,
+) -> Option<(usize, Vec<Completion>)> {
+    let completions = typst_ide::autocomplete(world, document, source, cursor, explicit);
+    let citations = citation_completions(world, source, cursor);
+    match (completions, citations) {
+        (Some((from, mut completions)), Some((citation_from, citations)))
+            if from == citation_from =>
+        {
+            // Keys of the compiled document are already completed by typst-ide
+            let citations: Vec<Completion> = citations
+                .into_iter()
+                .filter(|citation| !completions.iter().any(|c| c.label == citation.label))
+                .collect();
+            completions.extend(citations);
+            Some((from, completions))
+        }
+        (None, citations) => citations,
+        (completions, _) => completions,
+    }
+}
+
+/// Completes bibliography keys after `@` and within `cite(<...>)`. Returns the
+/// byte offset the completions apply from.
+pub fn citation_completions(
+    world: &ProjectWorld,
+    source: &Source,
+    cursor: usize,
+) -> Option<(usize, Vec<Completion>)> {
+    let from = citation_start(source, cursor)?;
+    let entries = bibliography(world);
+    if entries.is_empty() {
+        return None;
+    }
+
+    let completions = entries
+        .iter()
+        .map(|entry| Completion {
+            kind: CompletionKind::Constant,
+            label: entry.key.as_str().into(),
+            apply: None,
+            detail: Some(citation_detail(entry).into()),
+        })
+        .collect();
+    Some((from, completions))
+}
+
+fn citation_start(source: &Source, cursor: usize) -> Option<usize> {
+    let leaf = LinkedNode::new(source.root()).leaf_at(cursor)?;
+    match leaf.kind() {
+        SyntaxKind::RefMarker => return Some(leaf.offset() + 1),
+        SyntaxKind::Label
+            if leaf.parent_kind() == Some(SyntaxKind::Args) && cursor < leaf.range().end =>
+        {
+            return Some(leaf.offset() + 1)
+        }
+        _ => {}
+    }
+
+    // An unterminated label, e.g. `cite(<ke`, does not parse as a label
+    let before = source.text().get(..cursor)?;
+    let key = before.trim_end_matches(|c: char| c.is_alphanumeric() || "_-.:".contains(c));
+    let from = key.len();
+    if key.strip_suffix('<')?.trim_end().ends_with("cite(") {
+        Some(from)
+    } else {
+        None
+    }
+}
+
+/// Summarizes an entry as `Author et al. (Year): Title`.
+fn citation_detail(entry: &TypstBibliographyEntry) -> String {
+    let mut detail = match entry.authors.as_slice() {
+        [] => String::new(),
+        [author] => author.clone(),
+        [author, ..] => format!("{} et al.", author),
+    };
+    if let Some(year) = entry.year {
+        detail.push_str(&format!(" ({})", year));
+    }
+    if let Some(title) = &entry.title {
+        if !detail.is_empty() {
+            detail.push_str(": ");
+        }
+        detail.push_str(title);
+    }
+    detail.trim_start().to_string()
+}
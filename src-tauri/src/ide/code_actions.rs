@@ -0,0 +1,327 @@
+use crate::ide::utf16_range;
+use crate::ipc::{TypstCodeAction, TypstFileEdit, TypstTextEdit, TypstWorkspaceEdit};
+use crate::project::ProjectWorld;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use typst::diag::SourceDiagnostic;
+use typst::syntax::{ast, FileId, LinkedNode, Source, SyntaxKind};
+use typst::World;
+
+/// Opening delimiters with their closing counterparts. Delimiters which start
+/// with another delimiter come first.
+const DELIMITERS: [(&str, &str); 11] = [
+    ("/*", "*/"),
+    ("```", "```"),
+    ("`", "`"),
+    ("\"", "\""),
+    ("<", ">"),
+    ("(", ")"),
+    ("[", "]"),
+    ("{", "}"),
+    ("$", "$"),
+    ("*", "*"),
+    ("_", "_"),
+];
+
+/// Delimiters whose content is usually a single line.
+const LINE_DELIMITERS: [&str; 3] = ["`", "\"", "<"];
+
+/// Extensions of missing files which can be created. Empty files of other
+/// types, such as images, would fail to load as well.
+const CREATABLE_EXTENSIONS: [&str; 4] = ["typ", "bib", "yml", "yaml"];
+
+/// A fix for a diagnostic, with edits to its source as byte ranges.
+struct Fix {
+    title: String,
+    edits: Vec<(Range<usize>, String)>,
+    create_file: Option<PathBuf>,
+}
+
+/// Derives fixes from the diagnostics which are located in the source and
+/// overlap the byte range. The diagnostics usually stem from the last
+/// compilation, so diagnostics whose spans no longer exist are skipped.
+///
+/// Fixes import bindings from other sources of the project, close unclosed
+/// delimiters, create missing files, label headings for broken references and
+/// apply replacements suggested by hints, such as the names of renamed or
+/// deprecated functions.
+pub fn code_actions(
+    world: &ProjectWorld,
+    source: &Source,
+    range: Range<usize>,
+    diagnostics: &[SourceDiagnostic],
+) -> Vec<TypstCodeAction> {
+    let root = LinkedNode::new(source.root());
+    let path = source.id().vpath().as_rooted_path().to_path_buf();
+
+    let mut actions = vec![];
+    for diagnostic in diagnostics {
+        if diagnostic.span.id() != Some(source.id()) {
+            continue;
+        }
+        let Some(node) = root.find(diagnostic.span) else {
+            continue;
+        };
+        let node_range = node.range();
+        if node_range.end < range.start || node_range.start > range.end {
+            continue;
+        }
+
+        let message = diagnostic.message.as_str();
+        let mut fixes = vec![];
+        if let Some(name) = message.strip_prefix("unknown variable: ") {
+            fixes.extend(import_fixes(world, source, name));
+        }
+        if message.starts_with("unclosed ") {
+            fixes.extend(close_fix(source, &node));
+        }
+        if message.starts_with("file not found") {
+            fixes.extend(create_file_fix(source, &node));
+        }
+        if let Some(label) = missing_label(message) {
+            fixes.extend(label_fix(source, &root, node_range.start, label));
+        }
+        fixes.extend(
+            diagnostic
+                .hints
+                .iter()
+                .filter_map(|hint| hint_fix(&node, hint)),
+        );
+
+        for fix in fixes {
+            let edits: Vec<TypstTextEdit> = fix
+                .edits
+                .into_iter()
+                .map(|(range, text)| TypstTextEdit {
+                    range: utf16_range(source, range),
+                    text,
+                })
+                .collect();
+            let files = match edits.is_empty() {
+                true => vec![],
+                false => vec![TypstFileEdit {
+                    path: path.clone(),
+                    edits,
+                }],
+            };
+            actions.push(TypstCodeAction {
+                title: fix.title,
+                message: message.to_string(),
+                range: utf16_range(source, node_range.clone()),
+                create_file: fix.create_file,
+                edit: TypstWorkspaceEdit { files },
+            });
+        }
+    }
+    actions
+}
+
+/// Imports a binding from each other source of the project which defines it
+/// at the top level.
+fn import_fixes(world: &ProjectWorld, source: &Source, name: &str) -> Vec<Fix> {
+    let mut fixes = vec![];
+    for id in world.source_ids() {
+        if id == source.id() {
+            continue;
+        }
+        let Ok(other) = world.source(id) else {
+            continue;
+        };
+        if !defines(&other, name) {
+            continue;
+        }
+
+        let path = import_path(
+            source.id().vpath().as_rootless_path(),
+            id.vpath().as_rootless_path(),
+        );
+        fixes.push(Fix {
+            title: format!("Import `{}` from \"{}\"", name, path),
+            edits: vec![import_edit(source, id, &path, name)],
+            create_file: None,
+        });
+    }
+    fixes
+}
+
+fn defines(source: &Source, name: &str) -> bool {
+    source
+        .root()
+        .children()
+        .filter_map(|child| child.cast::<ast::LetBinding>())
+        .any(|binding| match binding.kind() {
+            ast::LetBindingKind::Closure(ident) => ident.as_str() == name,
+            ast::LetBindingKind::Normal(pattern) => pattern
+                .bindings()
+                .iter()
+                .any(|ident| ident.as_str() == name),
+        })
+}
+
+/// Writes the path of an imported file relative to the importing file if it is
+/// in the same directory or below, and relative to the project root otherwise.
+fn import_path(from: &Path, to: &Path) -> String {
+    let dir = from.parent().unwrap_or(Path::new(""));
+    let path = match to.strip_prefix(dir) {
+        Ok(relative) => relative.to_path_buf(),
+        Err(_) => Path::new("/").join(to),
+    };
+    path.to_string_lossy().replace('\\', "/")
+}
+
+/// Adds the name to an existing import of the file, or inserts a new import
+/// after the last import at the top level of the source.
+fn import_edit(source: &Source, id: FileId, path: &str, name: &str) -> (Range<usize>, String) {
+    let root = LinkedNode::new(source.root());
+    let imports: Vec<LinkedNode> = root
+        .children()
+        .filter(|child| child.kind() == SyntaxKind::ModuleImport)
+        .collect();
+
+    for import in &imports {
+        let Some(module_import) = import.cast::<ast::ModuleImport>() else {
+            continue;
+        };
+        let ast::Expr::Str(str) = module_import.source() else {
+            continue;
+        };
+        if source.id().join(&str.get()) != id {
+            continue;
+        }
+        if let Some(ast::Imports::Items(items)) = module_import.imports() {
+            if let Some(items) = import.find(items.span()) {
+                let text = items.get().clone().into_text();
+                let end = items.offset() + text.trim_end().len();
+                let separator = if text.trim_end().ends_with(',') {
+                    " "
+                } else {
+                    ", "
+                };
+                return (end..end, format!("{}{}", separator, name));
+            }
+        }
+    }
+
+    match imports.last() {
+        Some(last) => {
+            let end = last.range().end;
+            let line_end = source.text()[end..]
+                .find('\n')
+                .map_or(source.text().len(), |i| end + i);
+            (
+                line_end..line_end,
+                format!("\n#import \"{}\": {}", path, name),
+            )
+        }
+        None => (0..0, format!("#import \"{}\": {}\n", path, name)),
+    }
+}
+
+/// Inserts the closing delimiter of an unclosed node. Unclosed strings, raw
+/// text and comments are reported as a whole, while other delimiters are
+/// reported on their own and enclose the rest of their parent.
+fn close_fix(source: &Source, node: &LinkedNode) -> Option<Fix> {
+    let text = node.get().clone().into_text();
+    let (open, close) = DELIMITERS.iter().find(|(open, _)| text.starts_with(open))?;
+    let scope = match text.len() > open.len() {
+        true => node.range(),
+        false => node.parent()?.range(),
+    };
+
+    let mut content = &source.text()[scope.clone()];
+    if LINE_DELIMITERS.contains(open) {
+        content = content.lines().next().unwrap_or_default();
+    }
+    let end = scope.start + content.trim_end().len();
+    Some(Fix {
+        title: format!("Insert closing `{}`", close),
+        edits: vec![(end..end, close.to_string())],
+        create_file: None,
+    })
+}
+
+/// Creates the file of an `include`, `import` or other path argument which
+/// could not be found.
+fn create_file_fix(source: &Source, node: &LinkedNode) -> Option<Fix> {
+    let path = node.cast::<ast::Str>()?.get();
+    if path.starts_with('@') {
+        return None;
+    }
+    let extension = Path::new(path.as_str()).extension()?.to_str()?;
+    if !CREATABLE_EXTENSIONS.contains(&extension) {
+        return None;
+    }
+
+    let id = source.id().join(&path);
+    Some(Fix {
+        title: format!("Create \"{}\"", path),
+        edits: vec![],
+        create_file: Some(id.vpath().as_rooted_path().to_path_buf()),
+    })
+}
+
+fn missing_label(message: &str) -> Option<&str> {
+    message
+        .strip_prefix("label `<")?
+        .strip_suffix(">` does not exist in the document")
+}
+
+/// Attaches the missing label to the closest heading before the reference,
+/// unless that heading is already labelled.
+fn label_fix(source: &Source, root: &LinkedNode, offset: usize, label: &str) -> Option<Fix> {
+    let heading = last_heading_before(root, offset)?;
+    if heading
+        .next_sibling()
+        .is_some_and(|sibling| sibling.kind() == SyntaxKind::Label)
+    {
+        return None;
+    }
+
+    let range = heading.range();
+    let end = range.start + source.text()[range].trim_end().len();
+    let body = heading
+        .children()
+        .find(|child| child.kind() == SyntaxKind::Markup)?
+        .get()
+        .clone()
+        .into_text();
+    Some(Fix {
+        title: format!("Add label `<{}>` to heading \"{}\"", label, body.trim()),
+        edits: vec![(end..end, format!(" <{}>", label))],
+        create_file: None,
+    })
+}
+
+fn last_heading_before<'a>(node: &LinkedNode<'a>, offset: usize) -> Option<LinkedNode<'a>> {
+    let mut found = None;
+    for child in node.children() {
+        if child.offset() >= offset {
+            break;
+        }
+        if child.kind() == SyntaxKind::Heading {
+            found = Some(child);
+        } else if let Some(heading) = last_heading_before(&child, offset) {
+            found = Some(heading);
+        }
+    }
+    found
+}
+
+/// Applies a hint which suggests code to replace the erroneous code with, such
+/// as "use `x` instead" or "try adding spaces between each letter: `a b`".
+fn hint_fix(node: &LinkedNode, hint: &str) -> Option<Fix> {
+    let replacement = match hint.strip_prefix("use `") {
+        Some(rest) => rest.split_once("` instead")?.0,
+        None if hint.starts_with("try ") => hint.rsplit_once(": `")?.1.strip_suffix('`')?,
+        None => return None,
+    };
+    if replacement == node.get().clone().into_text().as_str() {
+        return None;
+    }
+
+    Some(Fix {
+        title: format!("Replace with `{}`", replacement),
+        edits: vec![(node.range(), replacement.to_string())],
+        create_file: None,
+    })
+}
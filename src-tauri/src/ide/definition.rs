@@ -0,0 +1,288 @@
+use crate::ide::resolve_import;
+use crate::project::ProjectWorld;
+use std::ops::Range;
+use typst::foundations::Label;
+use typst::model::Document;
+use typst::syntax::ast::AstNode;
+use typst::syntax::{ast, FileId, LinkedNode, Source, SyntaxKind};
+use typst::World;
+
+/// Limits how many imports are followed, which also guards against cyclic imports.
+const MAX_IMPORT_DEPTH: usize = 8;
+
+/// The location of a definition, as a byte range within a source.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Definition {
+    pub id: FileId,
+    pub range: Range<usize>,
+}
+
+/// What a name is bound to.
+enum Binding {
+    Definition(Definition),
+    /// A module bound by `import "module.typ"` or `import "..." as name`.
+    Module(FileId),
+}
+
+impl Binding {
+    fn into_definition(self) -> Definition {
+        match self {
+            Binding::Definition(definition) => definition,
+            Binding::Module(id) => Definition { id, range: 0..0 },
+        }
+    }
+}
+
+/// Finds the definition of the item at the cursor. This resolves identifiers
+/// through let bindings, parameters and imports, `import` and `include` paths,
+/// and label references.
+///
+/// Passing a `document` (from a previous compilation) is optional. Labels are
+/// looked up in the sources of the project if the document is unavailable or
+/// does not contain the label.
+pub fn definition(
+    world: &ProjectWorld,
+    document: Option<&Document>,
+    source: &Source,
+    cursor: usize,
+) -> Option<Definition> {
+    let leaf = LinkedNode::new(source.root()).leaf_at(cursor)?;
+    match leaf.kind() {
+        SyntaxKind::RefMarker => {
+            label_definition(world, document, leaf.text().trim_start_matches('@'))
+        }
+        SyntaxKind::Label => {
+            let label = leaf.cast::<ast::Label>()?;
+            label_definition(world, document, label.get())
+        }
+        SyntaxKind::Str => match leaf.parent_kind()? {
+            SyntaxKind::ModuleImport | SyntaxKind::ModuleInclude => {
+                let path = leaf.cast::<ast::Str>()?.get();
+                let id = resolve_import(world, source.id(), &path)?;
+                Some(Definition { id, range: 0..0 })
+            }
+            _ => None,
+        },
+        SyntaxKind::Ident | SyntaxKind::MathIdent => ident_definition(world, source.id(), &leaf),
+        _ => None,
+    }
+}
+
+/// Finds the definition of an identifier, which must not be a binding itself.
+pub(super) fn ident_definition(
+    world: &ProjectWorld,
+    id: FileId,
+    leaf: &LinkedNode,
+) -> Option<Definition> {
+    let name = leaf.text().as_str();
+    let parent = leaf.parent()?;
+    match parent.kind() {
+        // `module.item`
+        SyntaxKind::FieldAccess if leaf.prev_sibling_kind() == Some(SyntaxKind::Dot) => {
+            let target = leaf.prev_sibling()?.prev_sibling()?;
+            if !matches!(target.kind(), SyntaxKind::Ident | SyntaxKind::MathIdent) {
+                return None;
+            }
+            match resolve_binding(world, id, &target, target.text(), 0)? {
+                Binding::Module(module) => {
+                    module_definition(world, module, name, 0).map(Binding::into_definition)
+                }
+                Binding::Definition(_) => None,
+            }
+        }
+        // `import "module.typ": item` or `import "module.typ": item as renamed`
+        SyntaxKind::ImportItems | SyntaxKind::RenamedImportItem => {
+            let mut ancestor = parent;
+            let import = loop {
+                if let Some(import) = ancestor.cast::<ast::ModuleImport>() {
+                    break import;
+                }
+                ancestor = ancestor.parent()?;
+            };
+            let module = import_module(world, id, import)?;
+            let original = match parent.cast::<ast::RenamedImportItem>() {
+                Some(renamed) => renamed.original_name(),
+                None => leaf.cast::<ast::Ident>()?,
+            };
+            module_definition(world, module, original.as_str(), 0).map(Binding::into_definition)
+        }
+        _ => resolve_binding(world, id, leaf, name, 0).map(Binding::into_definition),
+    }
+}
+
+/// Resolves a name lexically, by walking up the syntax tree from `node` and
+/// checking the preceding siblings, closure parameters and loop patterns.
+fn resolve_binding(
+    world: &ProjectWorld,
+    id: FileId,
+    node: &LinkedNode,
+    name: &str,
+    depth: usize,
+) -> Option<Binding> {
+    let mut current = node.clone();
+    loop {
+        let mut sibling = current.prev_sibling();
+        while let Some(node) = sibling {
+            if let Some(binding) = binding_in(world, id, &node, name, depth) {
+                return Some(binding);
+            }
+            sibling = node.prev_sibling();
+        }
+
+        let parent = current.parent()?.clone();
+        let bindings = if let Some(closure) = parent.cast::<ast::Closure>() {
+            closure_bindings(closure)
+        } else if let Some(for_loop) = parent.cast::<ast::ForLoop>() {
+            for_loop.pattern().bindings()
+        } else {
+            vec![]
+        };
+
+        if let Some(ident) = bindings.into_iter().find(|ident| ident.as_str() == name) {
+            let range = parent.find(ident.span())?.range();
+            return Some(Binding::Definition(Definition { id, range }));
+        }
+
+        current = parent;
+    }
+}
+
+/// The names bound by a closure, which are its parameters and its name.
+pub(super) fn closure_bindings<'a>(closure: ast::Closure<'a>) -> Vec<ast::Ident<'a>> {
+    closure
+        .params()
+        .children()
+        .flat_map(|param| match param {
+            ast::Param::Pos(pattern) => pattern.bindings(),
+            ast::Param::Named(named) => vec![named.name()],
+            ast::Param::Spread(spread) => spread.sink_ident().into_iter().collect(),
+        })
+        .chain(closure.name())
+        .collect()
+}
+
+/// Checks whether `node` is a let binding or an import which binds `name`.
+fn binding_in(
+    world: &ProjectWorld,
+    id: FileId,
+    node: &LinkedNode,
+    name: &str,
+    depth: usize,
+) -> Option<Binding> {
+    if let Some(binding) = node.cast::<ast::LetBinding>() {
+        let ident = binding
+            .kind()
+            .bindings()
+            .into_iter()
+            .find(|ident| ident.as_str() == name)?;
+        let range = node.find(ident.span())?.range();
+        return Some(Binding::Definition(Definition { id, range }));
+    }
+
+    let import = node.cast::<ast::ModuleImport>()?;
+    if let Some(new_name) = import.new_name() {
+        if new_name.as_str() == name {
+            return Some(Binding::Module(import_module(world, id, import)?));
+        }
+    }
+
+    match import.imports() {
+        Some(ast::Imports::Items(items)) => {
+            let item = items
+                .iter()
+                .find(|item| item.bound_name().as_str() == name)?;
+            let module = import_module(world, id, import)?;
+            module_definition(world, module, item.original_name().as_str(), depth + 1).or_else(
+                || {
+                    // Fall back to the import item if the module cannot be analyzed
+                    let range = node.find(item.bound_name().span())?.range();
+                    Some(Binding::Definition(Definition { id, range }))
+                },
+            )
+        }
+        Some(ast::Imports::Wildcard) => {
+            let module = import_module(world, id, import)?;
+            module_definition(world, module, name, depth + 1)
+        }
+        None if import.new_name().is_none() => {
+            // `import "utils.typ"` binds `utils`, and `import "@preview/pkg:0.1.0"` binds `pkg`
+            let ast::Expr::Str(path) = import.source() else {
+                return None;
+            };
+            let path = path.get();
+            let stem = match path.strip_prefix('@') {
+                Some(spec) => spec.split(['/', ':']).nth(1)?,
+                None => std::path::Path::new(path.as_str()).file_stem()?.to_str()?,
+            };
+            if stem != name {
+                return None;
+            }
+            import_module(world, id, import).map(Binding::Module)
+        }
+        None => None,
+    }
+}
+
+/// Finds the top-level binding of `name` in a module. Later bindings shadow
+/// earlier ones.
+fn module_definition(
+    world: &ProjectWorld,
+    module: FileId,
+    name: &str,
+    depth: usize,
+) -> Option<Binding> {
+    if depth > MAX_IMPORT_DEPTH {
+        return None;
+    }
+
+    let source = world.source(module).ok()?;
+    let root = LinkedNode::new(source.root());
+    let binding = root
+        .children()
+        .rev()
+        .find_map(|child| binding_in(world, module, &child, name, depth));
+    binding
+}
+
+fn import_module(world: &ProjectWorld, id: FileId, import: ast::ModuleImport) -> Option<FileId> {
+    let ast::Expr::Str(path) = import.source() else {
+        return None;
+    };
+    resolve_import(world, id, &path.get())
+}
+
+fn label_definition(
+    world: &ProjectWorld,
+    document: Option<&Document>,
+    name: &str,
+) -> Option<Definition> {
+    let located = document
+        .and_then(|doc| doc.introspector.query_label(Label::new(name)).ok())
+        .and_then(|content| {
+            let span = content.span();
+            let id = span.id()?;
+            let range = world.source(id).ok()?.range(span)?;
+            Some(Definition { id, range })
+        });
+    if located.is_some() {
+        return located;
+    }
+
+    world.source_ids().into_iter().find_map(|id| {
+        let source = world.source(id).ok()?;
+        let range = find_label(&LinkedNode::new(source.root()), name)?;
+        Some(Definition { id, range })
+    })
+}
+
+/// Finds a label attached to content. Labels passed as arguments, such as
+/// in `ref(<label>)`, are references rather than definitions and are skipped.
+fn find_label(node: &LinkedNode, name: &str) -> Option<Range<usize>> {
+    if let Some(label) = node.cast::<ast::Label>() {
+        if label.get() == name && node.parent_kind() != Some(SyntaxKind::Args) {
+            return Some(node.range());
+        }
+        return None;
+    }
+    node.children().find_map(|child| find_label(&child, name))
+}
@@ -0,0 +1,133 @@
+use crate::ipc::{TypstDocs, TypstDocsKind, TypstDocsMember, TypstParam};
+use typst::foundations::{CastInfo, Func, ParamInfo, Repr, Scope, Value};
+use typst::World;
+
+/// Reflects over the standard library to document the function, type or
+/// module at a dotted path, e.g. `text`, `calc.pow` or `str.split`. The empty
+/// path documents the global scope.
+pub fn docs(world: &dyn World, path: &str) -> Option<TypstDocs> {
+    let global = world.library().global.scope();
+    if path.is_empty() {
+        return Some(TypstDocs {
+            path: String::new(),
+            kind: TypstDocsKind::Module,
+            title: "Global".to_string(),
+            docs: String::new(),
+            params: vec![],
+            returns: vec![],
+            members: members(global),
+        });
+    }
+
+    let mut value = None;
+    for name in path.split('.') {
+        let scope = match value {
+            None => global,
+            Some(Value::Func(func)) => func.scope()?,
+            Some(Value::Type(ty)) => ty.scope(),
+            Some(Value::Module(module)) => module.scope(),
+            Some(_) => return None,
+        };
+        value = Some(scope.get(name)?);
+    }
+
+    let docs = match value? {
+        Value::Func(func) => TypstDocs {
+            path: path.to_string(),
+            kind: TypstDocsKind::Function,
+            title: func.title().unwrap_or(path).to_string(),
+            docs: func.docs().unwrap_or_default().to_string(),
+            params: func_params(func),
+            returns: func.returns().map(cast_types).unwrap_or_default(),
+            members: func.scope().map(members).unwrap_or_default(),
+        },
+        // Types are documented along with their constructor
+        Value::Type(ty) => {
+            let constructor = ty.constructor().ok();
+            TypstDocs {
+                path: path.to_string(),
+                kind: TypstDocsKind::Type,
+                title: ty.title().to_string(),
+                docs: ty.docs().to_string(),
+                params: constructor.as_ref().map(func_params).unwrap_or_default(),
+                returns: vec![],
+                members: members(ty.scope()),
+            }
+        }
+        Value::Module(module) => TypstDocs {
+            path: path.to_string(),
+            kind: TypstDocsKind::Module,
+            title: module.name().to_string(),
+            docs: String::new(),
+            params: vec![],
+            returns: vec![],
+            members: members(module.scope()),
+        },
+        _ => return None,
+    };
+    Some(docs)
+}
+
+fn members(scope: &Scope) -> Vec<TypstDocsMember> {
+    let mut members: Vec<TypstDocsMember> = scope
+        .iter()
+        .map(|(name, value)| TypstDocsMember {
+            name: name.to_string(),
+            kind: match value {
+                Value::Func(_) => TypstDocsKind::Function,
+                Value::Type(_) => TypstDocsKind::Type,
+                Value::Module(_) => TypstDocsKind::Module,
+                _ => TypstDocsKind::Value,
+            },
+        })
+        .collect();
+    members.sort_by(|a, b| a.name.cmp(&b.name));
+    members
+}
+
+fn func_params(func: &Func) -> Vec<TypstParam> {
+    func.params()
+        .unwrap_or_default()
+        .iter()
+        .map(map_param)
+        .collect()
+}
+
+fn map_param(param: &ParamInfo) -> TypstParam {
+    TypstParam {
+        name: param.name.to_string(),
+        docs: param.docs.to_string(),
+        types: cast_types(&param.input),
+        default: param.default.map(|default| default().repr().to_string()),
+        positional: param.positional,
+        named: param.named,
+        variadic: param.variadic,
+        required: param.required,
+        settable: param.settable,
+    }
+}
+
+/// Lists the types and values accepted by a parameter or returned by a
+/// function, e.g. `["auto", "length"]`.
+fn cast_types(info: &CastInfo) -> Vec<String> {
+    let mut types = vec![];
+    collect_types(info, &mut types);
+    types
+}
+
+fn collect_types(info: &CastInfo, types: &mut Vec<String>) {
+    let name = match info {
+        CastInfo::Any => "any".to_string(),
+        CastInfo::Value(value, _) => value.repr().to_string(),
+        CastInfo::Type(ty) => ty.short_name().to_string(),
+        CastInfo::Union(infos) => {
+            for info in infos {
+                collect_types(info, types);
+            }
+            return;
+        }
+    };
+    if !types.contains(&name) {
+        types.push(name);
+    }
+}
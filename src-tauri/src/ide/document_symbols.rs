@@ -0,0 +1,171 @@
+use crate::ide::utf16_range;
+use crate::ipc::{TypstDocumentSymbol, TypstDocumentSymbolKind};
+use std::ops::Range;
+use typst::syntax::{ast, LinkedNode, Source, SyntaxKind};
+
+/// Lists the headings, top-level `let` bindings, show rules and labels of a
+/// source from its syntax tree. Symbols are nested below the heading they
+/// follow.
+pub fn document_symbols(source: &Source) -> Vec<TypstDocumentSymbol> {
+    let mut symbols = vec![];
+    collect(source, &LinkedNode::new(source.root()), &mut symbols);
+
+    let mut roots = vec![];
+    // Headings which may still receive children, with their levels
+    let mut open: Vec<(usize, TypstDocumentSymbol)> = vec![];
+    for (level, symbol) in symbols {
+        match level {
+            Some(level) => {
+                close(&mut open, &mut roots, level);
+                open.push((level, symbol));
+            }
+            None => match open.last_mut() {
+                Some((_, heading)) => heading.children.push(symbol),
+                None => roots.push(symbol),
+            },
+        }
+    }
+    close(&mut open, &mut roots, 0);
+    roots
+}
+
+/// Closes the open headings of at least the given level. The range of a
+/// heading is extended to cover its children.
+fn close(
+    open: &mut Vec<(usize, TypstDocumentSymbol)>,
+    roots: &mut Vec<TypstDocumentSymbol>,
+    level: usize,
+) {
+    while open.last().is_some_and(|(other, _)| *other >= level) {
+        let (_, mut heading) = open.pop().unwrap();
+        if let Some(last) = heading.children.last() {
+            heading.range.end = heading.range.end.max(last.range.end);
+        }
+        match open.last_mut() {
+            Some((_, parent)) => parent.children.push(heading),
+            None => roots.push(heading),
+        }
+    }
+}
+
+/// Collects the symbols in document order, along with the levels of headings.
+fn collect(
+    source: &Source,
+    node: &LinkedNode,
+    symbols: &mut Vec<(Option<usize>, TypstDocumentSymbol)>,
+) {
+    let symbol = |name: &str, detail, kind, range: Range<usize>, selection: Range<usize>| {
+        TypstDocumentSymbol {
+            name: name.trim().to_string(),
+            detail,
+            kind,
+            range: utf16_range(source, range),
+            selection_range: utf16_range(source, selection),
+            children: vec![],
+        }
+    };
+
+    if let Some(heading) = node.cast::<ast::Heading>() {
+        let body = node
+            .children()
+            .find(|child| child.kind() == SyntaxKind::Markup);
+        if let Some(body) = body {
+            symbols.push((
+                Some(heading.depth().get()),
+                symbol(
+                    body.get().clone().into_text().as_str(),
+                    None,
+                    TypstDocumentSymbolKind::Heading,
+                    node.range(),
+                    body.range(),
+                ),
+            ));
+        }
+    } else if let Some(binding) = node.cast::<ast::LetBinding>() {
+        // Bindings in blocks or closures are not visible to the rest of the file
+        let is_top_level = node
+            .parent()
+            .is_some_and(|parent| parent.parent().is_none());
+        if is_top_level {
+            for (ident, detail, kind) in binding_symbols(binding) {
+                let Some(ident_node) = node.find(ident.span()) else {
+                    continue;
+                };
+                symbols.push((
+                    None,
+                    symbol(
+                        ident.as_str(),
+                        detail,
+                        kind,
+                        node.range(),
+                        ident_node.range(),
+                    ),
+                ));
+            }
+        }
+    } else if let Some(rule) = node.cast::<ast::ShowRule>() {
+        let name = match rule.selector() {
+            Some(selector) => format!("show {}", selector.to_untyped().clone().into_text()),
+            None => "show".to_string(),
+        };
+        let transform = rule.transform().to_untyped().clone().into_text();
+        let selection = node
+            .children()
+            .find(|child| child.kind() == SyntaxKind::Show)
+            .map_or(node.range(), |keyword| keyword.range());
+        symbols.push((
+            None,
+            symbol(
+                &name,
+                Some(transform.to_string()),
+                TypstDocumentSymbolKind::ShowRule,
+                node.range(),
+                selection,
+            ),
+        ));
+    } else if let Some(label) = node.cast::<ast::Label>() {
+        // Labels passed as arguments, such as in `ref(<label>)`, are references
+        if node.parent_kind() != Some(SyntaxKind::Args) {
+            symbols.push((
+                None,
+                symbol(
+                    label.get(),
+                    None,
+                    TypstDocumentSymbolKind::Label,
+                    node.range(),
+                    node.range(),
+                ),
+            ));
+        }
+    }
+
+    for child in node.children() {
+        collect(source, &child, symbols);
+    }
+}
+
+fn binding_symbols(
+    binding: ast::LetBinding,
+) -> Vec<(ast::Ident, Option<String>, TypstDocumentSymbolKind)> {
+    match binding.kind() {
+        ast::LetBindingKind::Closure(ident) => {
+            let params = match binding.init() {
+                Some(ast::Expr::Closure(closure)) => Some(
+                    closure
+                        .params()
+                        .to_untyped()
+                        .clone()
+                        .into_text()
+                        .to_string(),
+                ),
+                _ => None,
+            };
+            vec![(ident, params, TypstDocumentSymbolKind::Function)]
+        }
+        ast::LetBindingKind::Normal(pattern) => pattern
+            .bindings()
+            .into_iter()
+            .map(|ident| (ident, None, TypstDocumentSymbolKind::Variable))
+            .collect(),
+    }
+}
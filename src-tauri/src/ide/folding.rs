@@ -0,0 +1,107 @@
+use crate::ipc::{TypstFoldingKind, TypstFoldingRange};
+use std::ops::Range;
+use typst::syntax::{ast, LinkedNode, Source, SyntaxKind};
+
+/// Finds the foldable regions of a source from its syntax tree: sections
+/// below headings, code and content blocks, function calls, raw blocks and
+/// block comments spanning several lines.
+pub fn folding_ranges(source: &Source) -> Vec<TypstFoldingRange> {
+    let mut ranges = vec![];
+    collect(source, &LinkedNode::new(source.root()), &mut ranges);
+
+    // Only one range can start on each line, so the largest is kept
+    ranges.sort_by(|a, b| {
+        a.start_line
+            .cmp(&b.start_line)
+            .then(b.end_line.cmp(&a.end_line))
+    });
+    ranges.dedup_by_key(|range| range.start_line);
+    ranges
+}
+
+fn collect(source: &Source, node: &LinkedNode, ranges: &mut Vec<TypstFoldingRange>) {
+    match node.kind() {
+        SyntaxKind::Markup => section_ranges(source, node, ranges),
+        SyntaxKind::CodeBlock
+        | SyntaxKind::ContentBlock
+        | SyntaxKind::FuncCall
+        | SyntaxKind::Raw => push(source, node.range(), None, true, ranges),
+        SyntaxKind::BlockComment => push(
+            source,
+            node.range(),
+            Some(TypstFoldingKind::Comment),
+            false,
+            ranges,
+        ),
+        _ => {}
+    }
+
+    for child in node.children() {
+        collect(source, &child, ranges);
+    }
+}
+
+/// Folds the content after each heading of the markup up to the next heading
+/// of the same or a higher level.
+fn section_ranges(source: &Source, markup: &LinkedNode, ranges: &mut Vec<TypstFoldingRange>) {
+    let children: Vec<LinkedNode> = markup.children().collect();
+    let depth = |node: &LinkedNode| node.cast::<ast::Heading>().map(|heading| heading.depth());
+
+    for (i, child) in children.iter().enumerate() {
+        let Some(level) = depth(child) else {
+            continue;
+        };
+        let next = children[i + 1..]
+            .iter()
+            .position(|sibling| depth(sibling).is_some_and(|other| other <= level))
+            .map_or(children.len(), |position| i + 1 + position);
+
+        // Trailing whitespace belongs to the next section
+        let last = children[i..next]
+            .iter()
+            .rev()
+            .find(|sibling| !matches!(sibling.kind(), SyntaxKind::Space | SyntaxKind::Parbreak));
+        if let Some(last) = last {
+            push(
+                source,
+                child.offset()..last.range().end,
+                None,
+                false,
+                ranges,
+            );
+        }
+    }
+}
+
+/// Adds a range unless it is on a single line. The line of the closing
+/// delimiter of blocks stays visible if it contains nothing else.
+fn push(
+    source: &Source,
+    range: Range<usize>,
+    kind: Option<TypstFoldingKind>,
+    delimited: bool,
+    ranges: &mut Vec<TypstFoldingRange>,
+) {
+    let Some(text) = source.text().get(range.clone()) else {
+        return;
+    };
+    let content = match delimited {
+        true => text.trim_end_matches([')', ']', '}', '`']).trim_end(),
+        false => text.trim_end(),
+    };
+
+    let end = range.start + content.len();
+    let (Some(start_line), Some(end_line)) = (
+        source.byte_to_line(range.start),
+        source.byte_to_line(end.saturating_sub(1).max(range.start)),
+    ) else {
+        return;
+    };
+    if end_line > start_line {
+        ranges.push(TypstFoldingRange {
+            start_line,
+            end_line,
+            kind,
+        });
+    }
+}
@@ -0,0 +1,95 @@
+use crate::ide::{bibliography, location};
+use crate::ipc::{TypstLabel, TypstLocation};
+use crate::project::ProjectWorld;
+use comemo::Track;
+use std::collections::{BTreeMap, HashSet};
+use typst::model::{BibliographyElem, Document};
+use typst::syntax::{ast, FileId, LinkedNode, SyntaxKind};
+use typst::World;
+
+#[derive(Default)]
+struct LabelUses {
+    definitions: Vec<TypstLocation>,
+    references: Vec<TypstLocation>,
+}
+
+/// Indexes the label definitions and references of every source in the
+/// project. The index is built from the syntax trees, so it is available even
+/// if the document fails to compile.
+///
+/// Passing a `document` (from a previous compilation) is optional. Labels
+/// which are created by code are only known from the document, so references
+/// to them are reported as broken without it. Bibliography keys are read from
+/// the bibliography files of the project.
+pub fn labels(world: &ProjectWorld, document: Option<&Document>) -> Vec<TypstLabel> {
+    let mut index: BTreeMap<String, LabelUses> = BTreeMap::new();
+    for id in world.source_ids() {
+        if let Ok(source) = world.source(id) {
+            collect(world, id, &LinkedNode::new(source.root()), &mut index);
+        }
+    }
+
+    let mut known: HashSet<String> = bibliography(world)
+        .into_iter()
+        .map(|entry| entry.key)
+        .collect();
+    if let Some(doc) = document {
+        known.extend(
+            doc.introspector
+                .all()
+                .filter_map(|elem| elem.label())
+                .map(|label| label.as_str().to_string()),
+        );
+        known.extend(
+            BibliographyElem::keys(doc.introspector.track())
+                .into_iter()
+                .map(|(key, _)| key.to_string()),
+        );
+    }
+
+    index
+        .into_iter()
+        .map(|(name, uses)| TypstLabel {
+            duplicate: uses.definitions.len() > 1,
+            unused: !uses.definitions.is_empty() && uses.references.is_empty(),
+            broken: uses.definitions.is_empty() && !known.contains(&name),
+            name,
+            definitions: uses.definitions,
+            references: uses.references,
+        })
+        .collect()
+}
+
+fn collect(
+    world: &ProjectWorld,
+    id: FileId,
+    node: &LinkedNode,
+    index: &mut BTreeMap<String, LabelUses>,
+) {
+    if let Some(label) = node.cast::<ast::Label>() {
+        if let Some(location) = location(world, id, node.range()) {
+            let uses = index.entry(label.get().to_string()).or_default();
+            // Labels passed as arguments, such as in `ref(<label>)`, are references
+            if node.parent_kind() == Some(SyntaxKind::Args) {
+                uses.references.push(location);
+            } else {
+                uses.definitions.push(location);
+            }
+        }
+        return;
+    }
+
+    if let Some(reference) = node.cast::<ast::Ref>() {
+        let marker = node
+            .children()
+            .find(|child| child.kind() == SyntaxKind::RefMarker);
+        if let Some(location) = marker.and_then(|marker| location(world, id, marker.range())) {
+            let uses = index.entry(reference.target().to_string()).or_default();
+            uses.references.push(location);
+        }
+    }
+
+    for child in node.children() {
+        collect(world, id, &child, index);
+    }
+}
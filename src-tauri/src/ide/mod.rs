@@ -0,0 +1,92 @@
+mod bibliography;
+mod code_actions;
+mod definition;
+mod docs;
+mod document_symbols;
+mod folding;
+mod labels;
+mod outline;
+mod rename;
+mod semantic_tokens;
+mod statistics;
+mod symbols;
+
+pub use bibliography::*;
+pub use code_actions::*;
+pub use definition::*;
+pub use docs::*;
+pub use document_symbols::*;
+pub use folding::*;
+pub use labels::*;
+pub use outline::*;
+pub use rename::*;
+pub use semantic_tokens::*;
+pub use statistics::*;
+pub use symbols::*;
+
+use crate::ipc::TypstLocation;
+use std::ops::Range;
+use std::str::FromStr;
+use typst::syntax::package::PackageSpec;
+use typst::syntax::{FileId, Source, Span, VirtualPath};
+use typst::World;
+
+/// Converts a byte range within a source to a [TypstLocation] with a UTF-16 range.
+pub fn location(world: &dyn World, id: FileId, range: Range<usize>) -> Option<TypstLocation> {
+    let source = world.source(id).ok()?;
+    let start = source.byte_to_utf16(range.start)?;
+    let end = source.byte_to_utf16(range.end)?;
+
+    Some(TypstLocation {
+        path: id.vpath().as_rooted_path().to_path_buf(),
+        package: id.package().map(|spec| spec.to_string()),
+        range: start..end,
+    })
+}
+
+/// Converts a byte range within a source to a range in UTF-16 code units,
+/// which is what the editor uses for offsets.
+pub fn utf16_range(source: &Source, range: Range<usize>) -> Range<usize> {
+    let start = source.byte_to_utf16(range.start).unwrap_or_default();
+    let end = source.byte_to_utf16(range.end).unwrap_or(start);
+    start..end
+}
+
+/// Converts the span of an element to a [TypstLocation], if it is attached to a source.
+pub fn span_location(world: &dyn World, span: Span) -> Option<TypstLocation> {
+    let id = span.id()?;
+    let range = world.source(id).ok()?.range(span)?;
+    location(world, id, range)
+}
+
+/// Resolves the path of an `import` or `include` relative to the importing file.
+/// Package imports resolve to the entrypoint of the package.
+pub fn resolve_import(world: &dyn World, current: FileId, path: &str) -> Option<FileId> {
+    if path.starts_with('@') {
+        let spec = PackageSpec::from_str(path).ok()?;
+        return package_entrypoint(world, spec);
+    }
+    Some(current.join(path))
+}
+
+/// Reads the entrypoint from the manifest of a package. This only scans for the
+/// `entrypoint` key in the `[package]` table, rather than parsing the whole manifest.
+fn package_entrypoint(world: &dyn World, spec: PackageSpec) -> Option<FileId> {
+    let manifest_id = FileId::new(Some(spec), VirtualPath::new("typst.toml"));
+    let bytes = world.file(manifest_id).ok()?;
+    let manifest = std::str::from_utf8(&bytes).ok()?;
+
+    let mut table = "";
+    for line in manifest.lines().map(str::trim) {
+        if line.starts_with('[') {
+            table = line;
+        } else if table == "[package]" {
+            if let Some((key, value)) = line.split_once('=') {
+                if key.trim() == "entrypoint" {
+                    return Some(manifest_id.join(value.trim().trim_matches('"')));
+                }
+            }
+        }
+    }
+    None
+}
@@ -0,0 +1,110 @@
+use crate::ide::span_location;
+use crate::ipc::{TypstOutlineItem, TypstOutlineKind};
+use comemo::Track;
+use typst::engine::{Engine, Route};
+use typst::eval::Tracer;
+use typst::foundations::{Content, Context, Smart, StyleChain};
+use typst::introspection::Locator;
+use typst::math::EquationElem;
+use typst::model::{Document, FigureElem, FigureKind, HeadingElem, Refable, TableElem};
+use typst::World;
+
+/// Builds the outline of a compiled document from its introspector. Headings
+/// are nested by their level, while figures, tables and labelled equations are
+/// placed under the heading they follow.
+pub fn outline(world: &dyn World, document: &Document) -> Vec<TypstOutlineItem> {
+    let mut locator = Locator::new();
+    let mut tracer = Tracer::new();
+    let mut engine = Engine {
+        world: world.track(),
+        route: Route::default(),
+        introspector: document.introspector.track(),
+        locator: &mut locator,
+        tracer: tracer.track_mut(),
+    };
+
+    let mut items = vec![];
+    // Elements are stored in document order
+    for elem in document.introspector.all() {
+        if let Some(item) = outline_item(&mut engine, world, document, elem) {
+            insert(&mut items, item, 0);
+        }
+    }
+    items
+}
+
+fn outline_item(
+    engine: &mut Engine,
+    world: &dyn World,
+    document: &Document,
+    elem: &Content,
+) -> Option<TypstOutlineItem> {
+    // Elements in the introspector are materialized, so their fields can be
+    // read without the original styles.
+    let styles = StyleChain::default();
+    let (kind, title, level) = if let Some(heading) = elem.to_packed::<HeadingElem>() {
+        let level = heading.resolve_level(styles).get();
+        (
+            TypstOutlineKind::Heading,
+            heading.body().plain_text(),
+            level,
+        )
+    } else if let Some(figure) = elem.to_packed::<FigureElem>() {
+        let kind = match figure.kind(styles) {
+            Smart::Custom(FigureKind::Elem(func)) if func == TableElem::elem() => {
+                TypstOutlineKind::Table
+            }
+            _ => TypstOutlineKind::Figure,
+        };
+        let title = figure
+            .caption(styles)
+            .map(|caption| caption.body().plain_text())
+            .unwrap_or_default();
+        (kind, title, 0)
+    } else if let Some(equation) = elem.to_packed::<EquationElem>() {
+        // Only labelled equations can be referenced, which makes them worth listing
+        elem.label()?;
+        (TypstOutlineKind::Equation, equation.body().plain_text(), 0)
+    } else {
+        return None;
+    };
+
+    let loc = elem.location()?;
+    let numbering = elem.with::<dyn Refable>().and_then(|refable| {
+        let numbering = refable.numbering()?;
+        let context = Context::new(Some(loc), Some(styles));
+        let state = refable.counter().at_loc(engine, loc).ok()?;
+        let value = state.display(engine, context.track(), numbering).ok()?;
+        Some(value.display().plain_text().to_string())
+    });
+
+    Some(TypstOutlineItem {
+        kind,
+        title: title.to_string(),
+        level,
+        numbering,
+        label: elem.label().map(|label| label.as_str().to_string()),
+        position: document.introspector.position(loc).into(),
+        location: span_location(world, elem.span()),
+        children: vec![],
+    })
+}
+
+/// Inserts an item below the last heading of `items` if it belongs there.
+fn insert(items: &mut Vec<TypstOutlineItem>, mut item: TypstOutlineItem, depth: usize) {
+    let is_heading = matches!(item.kind, TypstOutlineKind::Heading);
+    match items.last_mut() {
+        Some(last)
+            if matches!(last.kind, TypstOutlineKind::Heading)
+                && (!is_heading || last.level < item.level) =>
+        {
+            insert(&mut last.children, item, depth + 1)
+        }
+        _ => {
+            if !is_heading {
+                item.level = depth + 1;
+            }
+            items.push(item);
+        }
+    }
+}
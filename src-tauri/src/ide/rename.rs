@@ -0,0 +1,233 @@
+use crate::ide::definition::{closure_bindings, ident_definition};
+use crate::ide::{utf16_range, Definition};
+use crate::ipc::{TypstFileEdit, TypstTextEdit, TypstWorkspaceEdit};
+use crate::project::ProjectWorld;
+use std::collections::BTreeMap;
+use std::ops::Range;
+use typst::syntax::{ast, is_ident, FileId, LinkedNode, Source, SyntaxKind};
+use typst::World;
+
+#[derive(thiserror::Error, Debug)]
+pub enum RenameError {
+    #[error("there is nothing to rename at the cursor")]
+    NoTarget,
+    #[error("`{0}` is not a valid name")]
+    InvalidName(String),
+    #[error("`{0}` cannot be referenced, as a trailing `.` or `:` ends a reference")]
+    UnreferenceableLabel(String),
+}
+
+enum RenameTarget {
+    Label(String),
+    Binding {
+        name: String,
+        definition: Definition,
+    },
+}
+
+/// Renames the label or binding at the cursor in all sources of the project.
+/// Occurrences are found through the syntax trees, so text in strings,
+/// comments and markup is left untouched.
+///
+/// Bindings can only be renamed if they are defined in the project, rather
+/// than in a package or the standard library.
+pub fn rename(
+    world: &ProjectWorld,
+    source: &Source,
+    cursor: usize,
+    new_name: &str,
+) -> Result<TypstWorkspaceEdit, RenameError> {
+    let target = rename_target(world, source, cursor).ok_or(RenameError::NoTarget)?;
+    let valid = match target {
+        RenameTarget::Label(_) => is_label(new_name),
+        RenameTarget::Binding { .. } => is_ident(new_name),
+    };
+    if !valid {
+        return Err(RenameError::InvalidName(new_name.to_string()));
+    }
+
+    let mut files = vec![];
+    let mut referenced = false;
+    for id in world.source_ids() {
+        let Ok(source) = world.source(id) else {
+            continue;
+        };
+
+        let mut ranges = BTreeMap::new();
+        collect(
+            world,
+            id,
+            &LinkedNode::new(source.root()),
+            &target,
+            &mut ranges,
+        );
+        if ranges.is_empty() {
+            continue;
+        }
+        referenced |= ranges
+            .values()
+            .any(|(_, kind)| *kind == SyntaxKind::RefMarker);
+
+        let edits = ranges
+            .into_values()
+            .map(|(range, kind)| TypstTextEdit {
+                range: utf16_range(&source, range),
+                text: match kind {
+                    SyntaxKind::Label => format!("<{}>", new_name),
+                    SyntaxKind::RefMarker => format!("@{}", new_name),
+                    _ => new_name.to_string(),
+                },
+            })
+            .collect();
+        files.push(TypstFileEdit {
+            path: id.vpath().as_rooted_path().to_path_buf(),
+            edits,
+        });
+    }
+
+    // `@label.` references `label`, followed by a period
+    if referenced && new_name.ends_with(['.', ':']) {
+        return Err(RenameError::UnreferenceableLabel(new_name.to_string()));
+    }
+
+    Ok(TypstWorkspaceEdit { files })
+}
+
+fn rename_target(world: &ProjectWorld, source: &Source, cursor: usize) -> Option<RenameTarget> {
+    let leaf = LinkedNode::new(source.root()).leaf_at(cursor)?;
+    match leaf.kind() {
+        SyntaxKind::RefMarker => Some(RenameTarget::Label(
+            leaf.text().trim_start_matches('@').to_string(),
+        )),
+        SyntaxKind::Label => Some(RenameTarget::Label(
+            leaf.cast::<ast::Label>()?.get().to_string(),
+        )),
+        SyntaxKind::Ident | SyntaxKind::MathIdent => {
+            let definition = binding_definition(world, source.id(), &leaf)?;
+            if definition.id.package().is_some() || definition.range.is_empty() {
+                return None;
+            }
+            Some(RenameTarget::Binding {
+                name: leaf.text().to_string(),
+                definition,
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Resolves an identifier to its definition, which is the identifier itself
+/// if it is bound at this position.
+fn binding_definition(world: &ProjectWorld, id: FileId, leaf: &LinkedNode) -> Option<Definition> {
+    if is_binding_site(leaf) {
+        return Some(Definition {
+            id,
+            range: leaf.range(),
+        });
+    }
+
+    // Names of named arguments and dictionary keys are not variables
+    if let Some(named) = leaf.parent().and_then(|parent| parent.cast::<ast::Named>()) {
+        if named.name().span() == leaf.span() {
+            return None;
+        }
+    }
+
+    // The new name in `import "module.typ": item as renamed` is a separate binding
+    if let Some(renamed) = leaf
+        .parent()
+        .and_then(|parent| parent.cast::<ast::RenamedImportItem>())
+    {
+        if renamed.new_name().span() == leaf.span() {
+            return None;
+        }
+    }
+
+    ident_definition(world, id, leaf)
+}
+
+/// Checks whether the identifier is bound by the closest enclosing let
+/// binding, closure or for loop.
+fn is_binding_site(leaf: &LinkedNode) -> bool {
+    let span = leaf.span();
+    let mut node = leaf.parent();
+    while let Some(current) = node {
+        let bindings = if let Some(binding) = current.cast::<ast::LetBinding>() {
+            binding.kind().bindings()
+        } else if let Some(closure) = current.cast::<ast::Closure>() {
+            closure_bindings(closure)
+        } else if let Some(for_loop) = current.cast::<ast::ForLoop>() {
+            for_loop.pattern().bindings()
+        } else {
+            node = current.parent();
+            continue;
+        };
+        return bindings.iter().any(|ident| ident.span() == span);
+    }
+    false
+}
+
+/// Collects the byte ranges of the occurrences of the target by their start,
+/// along with the kind of the node they belong to.
+fn collect(
+    world: &ProjectWorld,
+    id: FileId,
+    node: &LinkedNode,
+    target: &RenameTarget,
+    ranges: &mut BTreeMap<usize, (Range<usize>, SyntaxKind)>,
+) {
+    match (node.kind(), target) {
+        (SyntaxKind::Label, RenameTarget::Label(name)) => {
+            if node.cast::<ast::Label>().map(|label| label.get()) == Some(name.as_str()) {
+                ranges.insert(node.offset(), (node.range(), SyntaxKind::Label));
+            }
+        }
+        (SyntaxKind::RefMarker, RenameTarget::Label(name)) => {
+            if node.text().trim_start_matches('@') == name {
+                ranges.insert(node.offset(), (node.range(), SyntaxKind::RefMarker));
+            }
+        }
+        (SyntaxKind::Ident | SyntaxKind::MathIdent, RenameTarget::Binding { name, definition }) => {
+            if node.text() == name
+                && binding_definition(world, id, node).as_ref() == Some(definition)
+            {
+                ranges.insert(node.offset(), (node.range(), node.kind()));
+            }
+        }
+        _ => {}
+    }
+
+    for child in node.children() {
+        collect(world, id, &child, target, ranges);
+    }
+}
+
+/// Checks the characters which labels may consist of. Labels which are
+/// referenced must not end with `.` or `:`, which is checked separately.
+fn is_label(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | ':'))
+}
+
+/// Applies text edits with UTF-16 ranges to a source, which must not overlap.
+pub fn apply_edits(source: &Source, edits: &[TypstTextEdit]) -> String {
+    let text = source.text();
+    let byte = |offset: usize| source.utf16_to_byte(offset).unwrap_or(text.len());
+
+    let mut edits: Vec<&TypstTextEdit> = edits.iter().collect();
+    edits.sort_by_key(|edit| edit.range.start);
+
+    let mut result = String::with_capacity(text.len());
+    let mut last = 0;
+    for edit in edits {
+        let start = byte(edit.range.start).max(last);
+        let end = byte(edit.range.end).max(start);
+        result.push_str(&text[last..start]);
+        result.push_str(&edit.text);
+        last = end;
+    }
+    result.push_str(&text[last..]);
+    result
+}
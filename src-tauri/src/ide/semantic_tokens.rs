@@ -0,0 +1,301 @@
+use crate::ipc::TypstSemanticTokensEdit;
+use std::ops::Range;
+use typst::syntax::{ast, LinkedNode, Source, SyntaxKind};
+
+/// The token types, indexed by the types of the encoded tokens.
+pub const TOKEN_TYPES: &[&str] = &[
+    "comment",
+    "string",
+    "keyword",
+    "operator",
+    "number",
+    "constant",
+    "function",
+    "variable",
+    "parameter",
+    "escape",
+    "link",
+    "raw",
+    "label",
+    "ref",
+    "heading",
+    "marker",
+    "punctuation",
+    "text",
+];
+
+/// The token modifiers, indexed by the bits of the encoded modifier sets.
+pub const TOKEN_MODIFIERS: &[&str] = &["strong", "emph", "math"];
+
+#[derive(Clone, Copy)]
+enum TokenType {
+    Comment,
+    String,
+    Keyword,
+    Operator,
+    Number,
+    Constant,
+    Function,
+    Variable,
+    Parameter,
+    Escape,
+    Link,
+    Raw,
+    Label,
+    Ref,
+    Heading,
+    Marker,
+    Punctuation,
+    Text,
+}
+
+const MODIFIER_STRONG: u32 = 1 << 0;
+const MODIFIER_EMPH: u32 = 1 << 1;
+const MODIFIER_MATH: u32 = 1 << 2;
+
+struct Token {
+    range: Range<usize>,
+    ty: TokenType,
+    modifiers: u32,
+}
+
+/// Computes the semantic tokens of a source from its syntax tree, encoded as
+/// in the Language Server Protocol: five integers per token, which are the
+/// line and start column relative to the previous token, the length, the index
+/// of the type in [TOKEN_TYPES] and the set of [TOKEN_MODIFIERS]. Columns and
+/// lengths are in UTF-16 code units.
+pub fn semantic_tokens(source: &Source) -> Vec<u32> {
+    let mut tokens = vec![];
+    collect(&LinkedNode::new(source.root()), 0, false, &mut tokens);
+
+    let mut data = Vec::with_capacity(tokens.len() * 5);
+    let (mut last_line, mut last_column) = (0, 0);
+    for token in tokens {
+        // Tokens may not span multiple lines, so multi-line tokens such as
+        // block comments and raw blocks are split at line breaks
+        for (line, range) in line_ranges(source, token.range) {
+            let line_start = source.line_to_byte(line).unwrap_or_default();
+            let column = utf16_len(source, line_start..range.start);
+            let length = utf16_len(source, range);
+            if length == 0 {
+                continue;
+            }
+
+            let delta_line = line - last_line;
+            let delta_column = if delta_line == 0 {
+                column - last_column
+            } else {
+                column
+            };
+            data.extend([
+                delta_line as u32,
+                delta_column as u32,
+                length as u32,
+                token.ty as u32,
+                token.modifiers,
+            ]);
+            (last_line, last_column) = (line, column);
+        }
+    }
+    data
+}
+
+/// Describes how to turn `previous` into `current`. Tokens before and after an
+/// edit usually stay the same, so a single edit replaces what lies between the
+/// common prefix and suffix.
+pub fn semantic_token_edits(previous: &[u32], current: &[u32]) -> Vec<TypstSemanticTokensEdit> {
+    let prefix = previous
+        .iter()
+        .zip(current)
+        .take_while(|(a, b)| a == b)
+        .count();
+    let suffix = previous[prefix..]
+        .iter()
+        .rev()
+        .zip(current[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let delete_count = previous.len() - prefix - suffix;
+    let data = current[prefix..current.len() - suffix].to_vec();
+    if delete_count == 0 && data.is_empty() {
+        return vec![];
+    }
+    vec![TypstSemanticTokensEdit {
+        start: prefix,
+        delete_count,
+        data,
+    }]
+}
+
+fn collect(node: &LinkedNode, modifiers: u32, heading: bool, tokens: &mut Vec<Token>) {
+    let modifiers = modifiers
+        | match node.kind() {
+            SyntaxKind::Strong => MODIFIER_STRONG,
+            SyntaxKind::Emph => MODIFIER_EMPH,
+            SyntaxKind::Equation => MODIFIER_MATH,
+            _ => 0,
+        };
+    let heading = heading || node.kind() == SyntaxKind::Heading;
+
+    if node.children().len() == 0 {
+        if let Some(ty) = token_type(node, modifiers, heading) {
+            tokens.push(Token {
+                range: node.range(),
+                ty,
+                modifiers,
+            });
+        }
+        return;
+    }
+
+    for child in node.children() {
+        collect(&child, modifiers, heading, tokens);
+    }
+}
+
+fn token_type(leaf: &LinkedNode, modifiers: u32, heading: bool) -> Option<TokenType> {
+    if leaf.parent_kind() == Some(SyntaxKind::Raw) {
+        return Some(TokenType::Raw);
+    }
+
+    Some(match leaf.kind() {
+        SyntaxKind::LineComment | SyntaxKind::BlockComment => TokenType::Comment,
+        SyntaxKind::Str => TokenType::String,
+        SyntaxKind::Int | SyntaxKind::Float | SyntaxKind::Numeric => TokenType::Number,
+        SyntaxKind::Bool | SyntaxKind::None | SyntaxKind::Auto => TokenType::Constant,
+        SyntaxKind::Let
+        | SyntaxKind::Set
+        | SyntaxKind::Show
+        | SyntaxKind::Context
+        | SyntaxKind::If
+        | SyntaxKind::Else
+        | SyntaxKind::For
+        | SyntaxKind::In
+        | SyntaxKind::While
+        | SyntaxKind::Break
+        | SyntaxKind::Continue
+        | SyntaxKind::Return
+        | SyntaxKind::Import
+        | SyntaxKind::Include
+        | SyntaxKind::As
+        | SyntaxKind::Not
+        | SyntaxKind::And
+        | SyntaxKind::Or => TokenType::Keyword,
+        // Delimiters of strong and emphasized markup
+        SyntaxKind::Star | SyntaxKind::Underscore
+            if matches!(
+                leaf.parent_kind(),
+                Some(SyntaxKind::Strong | SyntaxKind::Emph)
+            ) =>
+        {
+            TokenType::Punctuation
+        }
+        SyntaxKind::Plus
+        | SyntaxKind::Minus
+        | SyntaxKind::Star
+        | SyntaxKind::Slash
+        | SyntaxKind::Underscore
+        | SyntaxKind::Hat
+        | SyntaxKind::Prime
+        | SyntaxKind::Root
+        | SyntaxKind::MathAlignPoint
+        | SyntaxKind::Eq
+        | SyntaxKind::EqEq
+        | SyntaxKind::ExclEq
+        | SyntaxKind::Lt
+        | SyntaxKind::LtEq
+        | SyntaxKind::Gt
+        | SyntaxKind::GtEq
+        | SyntaxKind::PlusEq
+        | SyntaxKind::HyphEq
+        | SyntaxKind::StarEq
+        | SyntaxKind::SlashEq
+        | SyntaxKind::Dots
+        | SyntaxKind::Arrow => TokenType::Operator,
+        SyntaxKind::Escape | SyntaxKind::Shorthand => TokenType::Escape,
+        SyntaxKind::Link => TokenType::Link,
+        SyntaxKind::Label => TokenType::Label,
+        SyntaxKind::RefMarker => TokenType::Ref,
+        SyntaxKind::Ident | SyntaxKind::MathIdent => ident_type(leaf),
+        SyntaxKind::HeadingMarker | SyntaxKind::Text | SyntaxKind::SmartQuote if heading => {
+            TokenType::Heading
+        }
+        SyntaxKind::ListMarker | SyntaxKind::EnumMarker | SyntaxKind::TermMarker => {
+            TokenType::Marker
+        }
+        SyntaxKind::Hash
+        | SyntaxKind::Dollar
+        | SyntaxKind::LeftBrace
+        | SyntaxKind::RightBrace
+        | SyntaxKind::LeftBracket
+        | SyntaxKind::RightBracket
+        | SyntaxKind::LeftParen
+        | SyntaxKind::RightParen
+        | SyntaxKind::Comma
+        | SyntaxKind::Semicolon
+        | SyntaxKind::Colon
+        | SyntaxKind::Dot => TokenType::Punctuation,
+        // Plain text is only highlighted if it is strong or emphasized
+        SyntaxKind::Text | SyntaxKind::SmartQuote
+            if modifiers & (MODIFIER_STRONG | MODIFIER_EMPH) != 0 =>
+        {
+            TokenType::Text
+        }
+        _ => return None,
+    })
+}
+
+fn ident_type(leaf: &LinkedNode) -> TokenType {
+    let Some(parent) = leaf.parent() else {
+        return TokenType::Variable;
+    };
+
+    // Callees of calls, including methods, and targets of set rules
+    let is_callee = |node: &LinkedNode, callee: &LinkedNode| {
+        node.cast::<ast::FuncCall>()
+            .is_some_and(|call| call.callee().span() == callee.span())
+    };
+    let is_method = parent
+        .cast::<ast::FieldAccess>()
+        .is_some_and(|access| access.field().span() == leaf.span())
+        && parent
+            .parent()
+            .is_some_and(|grandparent| is_callee(grandparent, parent));
+    let is_set_target = parent
+        .cast::<ast::SetRule>()
+        .is_some_and(|rule| rule.target().span() == leaf.span());
+    if is_callee(parent, leaf) || is_method || is_set_target {
+        return TokenType::Function;
+    }
+
+    match parent.cast::<ast::Named>() {
+        Some(named) if named.name().span() == leaf.span() => TokenType::Parameter,
+        _ => TokenType::Variable,
+    }
+}
+
+/// Splits a byte range into the parts on each line, excluding line breaks.
+fn line_ranges(source: &Source, range: Range<usize>) -> Vec<(usize, Range<usize>)> {
+    let (Some(first), Some(last)) = (
+        source.byte_to_line(range.start),
+        source.byte_to_line(range.end),
+    ) else {
+        return vec![];
+    };
+
+    (first..=last)
+        .filter_map(|line| {
+            let line_range = source.line_to_range(line)?;
+            let start = range.start.max(line_range.start);
+            let end = range.end.min(line_range.end);
+            let text = source.text().get(start..end)?;
+            let end = start + text.trim_end_matches(['\r', '\n']).len();
+            (start < end).then_some((line, start..end))
+        })
+        .collect()
+}
+
+fn utf16_len(source: &Source, range: Range<usize>) -> usize {
+    source.text()[range].encode_utf16().count()
+}
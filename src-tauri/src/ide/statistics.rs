@@ -0,0 +1,322 @@
+use crate::ide::utf16_range;
+use crate::ipc::{
+    TypstFileStatistics, TypstLocation, TypstSectionStatistics, TypstStatistics, TypstTextCount,
+    TypstTextStatistics,
+};
+use crate::project::ProjectWorld;
+use std::collections::{HashMap, HashSet};
+use typst::foundations::{Content, StyleChain};
+use typst::introspection::{Location, Meta};
+use typst::layout::{Frame, FrameItem, Point};
+use typst::model::{Document, HeadingElem};
+use typst::syntax::{ast, FileId, LinkedNode, Source, Span, SyntaxKind, SyntaxNode};
+use typst::text::TextItem;
+use typst::World;
+
+#[derive(Clone, Copy)]
+enum TextKind {
+    Body,
+    Heading,
+    Caption,
+    Bibliography,
+}
+
+/// Counts words as runs of non-whitespace characters which contain a letter
+/// or a digit, like most word processors do. Runs may span several pieces of
+/// text until [WordCounter::split] is called.
+#[derive(Default)]
+struct WordCounter {
+    /// Whether the current run has already been counted as a word.
+    counted: bool,
+}
+
+impl WordCounter {
+    fn count(&mut self, text: &str) -> TypstTextCount {
+        let mut count = TypstTextCount::default();
+        for c in text.chars() {
+            count.characters_with_spaces += 1;
+            if c.is_whitespace() {
+                self.counted = false;
+                continue;
+            }
+            count.characters += 1;
+            if !self.counted && c.is_alphanumeric() {
+                count.words += 1;
+                self.counted = true;
+            }
+        }
+        count
+    }
+
+    fn split(&mut self) {
+        self.counted = false;
+    }
+}
+
+/// The sources which statistics are computed from. Sources are cheap to clone,
+/// so they are collected while the world is locked and the statistics are
+/// computed without holding the lock.
+pub struct StatisticsSources {
+    /// The sources of the project, ordered by path.
+    files: Vec<FileId>,
+    sources: HashMap<FileId, Source>,
+}
+
+impl StatisticsSources {
+    /// Collects the sources of the project and the sources which the document
+    /// was compiled from, including those of packages.
+    pub fn collect(world: &ProjectWorld, dependencies: &HashSet<FileId>) -> Self {
+        let files = world.source_ids();
+        let sources = files
+            .iter()
+            .chain(dependencies)
+            .filter_map(|id| Some((*id, world.source(*id).ok()?)))
+            .collect();
+        Self { files, sources }
+    }
+
+    fn location(&self, span: Span) -> Option<TypstLocation> {
+        let id = span.id()?;
+        let source = self.sources.get(&id)?;
+        let range = source.range(span)?;
+        Some(TypstLocation {
+            path: id.vpath().as_rooted_path().to_path_buf(),
+            package: id.package().map(|spec| spec.to_string()),
+            range: utf16_range(source, range),
+        })
+    }
+}
+
+/// Computes the word and character counts of the compiled document, by page
+/// and by heading section, and of the markup in the sources of the project.
+///
+/// Text in the document is attributed to headings, captions or the
+/// bibliography by the syntax it was created from.
+pub fn statistics(sources: &StatisticsSources, document: Option<&Document>) -> TypstStatistics {
+    let mut statistics = TypstStatistics::default();
+
+    if let Some(document) = document {
+        let mut counter = DocumentCounter {
+            sources,
+            words: WordCounter::default(),
+            sections: vec![],
+            headings: HashSet::new(),
+        };
+        for page in &document.pages {
+            let mut page_statistics = TypstTextStatistics::default();
+            counter.words.split();
+            counter.frame(&page.frame, &mut page_statistics);
+
+            add_statistics(&mut statistics.document, &page_statistics);
+            statistics.pages.push(page_statistics);
+        }
+        statistics.sections = counter.sections;
+    }
+
+    for id in &sources.files {
+        let Some(source) = sources.sources.get(id) else {
+            continue;
+        };
+        let mut file_statistics = TypstTextStatistics::default();
+        count_markup(
+            source.root(),
+            false,
+            TextKind::Body,
+            &mut WordCounter::default(),
+            &mut file_statistics,
+        );
+        statistics.files.push(TypstFileStatistics {
+            path: id.vpath().as_rooted_path().to_path_buf(),
+            statistics: file_statistics,
+        });
+    }
+
+    statistics
+}
+
+struct DocumentCounter<'a> {
+    sources: &'a StatisticsSources,
+    words: WordCounter,
+    sections: Vec<TypstSectionStatistics>,
+    /// Headings which have started a section.
+    headings: HashSet<Location>,
+}
+
+impl DocumentCounter<'_> {
+    fn frame(&mut self, frame: &Frame, page: &mut TypstTextStatistics) {
+        // The end of the previous text on the same line, if it is part of this frame
+        let mut end: Option<Point> = None;
+        for (pos, item) in frame.items() {
+            match item {
+                FrameItem::Group(group) => {
+                    self.words.split();
+                    self.frame(&group.frame, page);
+                    end = None;
+                }
+                FrameItem::Text(text) => {
+                    // Differently styled runs of a word, e.g. in `un*believ*able`,
+                    // are separate items which touch each other
+                    let touches = end.is_some_and(|end| {
+                        (end.x - pos.x).to_pt().abs() < 0.1 && (end.y - pos.y).to_pt().abs() < 0.1
+                    });
+                    if !touches {
+                        self.words.split();
+                    }
+
+                    let count = self.words.count(&text.text);
+                    let kind = self.text_kind(text);
+                    add_count(kind_mut(page, kind), count);
+                    add_count(kind_mut(&mut self.section().statistics, kind), count);
+                    end = Some(Point::new(pos.x + text.width(), pos.y));
+                }
+                FrameItem::Meta(Meta::Elem(elem), _) => self.start_section(elem),
+                _ => {}
+            }
+        }
+    }
+
+    fn start_section(&mut self, elem: &Content) {
+        let Some(heading) = elem.to_packed::<HeadingElem>() else {
+            return;
+        };
+        // Elements may be laid out more than once, e.g. in page headers
+        if let Some(loc) = elem.location() {
+            if !self.headings.insert(loc) {
+                return;
+            }
+        }
+
+        self.sections.push(TypstSectionStatistics {
+            title: Some(heading.body().plain_text().to_string()),
+            level: heading.resolve_level(StyleChain::default()).get(),
+            location: self.sources.location(elem.span()),
+            statistics: TypstTextStatistics::default(),
+        });
+    }
+
+    /// Returns the current section, starting one for the text before the first
+    /// heading if necessary.
+    fn section(&mut self) -> &mut TypstSectionStatistics {
+        if self.sections.is_empty() {
+            self.sections.push(TypstSectionStatistics {
+                title: None,
+                level: 0,
+                location: None,
+                statistics: TypstTextStatistics::default(),
+            });
+        }
+        self.sections.last_mut().unwrap()
+    }
+
+    fn text_kind(&self, text: &TextItem) -> TextKind {
+        let Some(span) = text.glyphs.first().map(|glyph| glyph.span.0) else {
+            return TextKind::Body;
+        };
+        let Some(source) = span.id().and_then(|id| self.sources.sources.get(&id)) else {
+            return TextKind::Body;
+        };
+
+        let root = LinkedNode::new(source.root());
+        let mut node = root.find(span);
+        while let Some(current) = node {
+            match syntax_kind(current.get()) {
+                Some(kind) => return kind,
+                None => node = current.parent().cloned(),
+            }
+        }
+        TextKind::Body
+    }
+}
+
+/// Determines the kind of the text created by a node and its descendants, if
+/// the node determines it.
+fn syntax_kind(node: &SyntaxNode) -> Option<TextKind> {
+    if node.kind() == SyntaxKind::Heading || is_call(node, "heading") {
+        return Some(TextKind::Heading);
+    }
+    if is_call(node, "bibliography") {
+        return Some(TextKind::Bibliography);
+    }
+    match node.cast::<ast::Named>() {
+        Some(named) if named.name().as_str() == "caption" => Some(TextKind::Caption),
+        _ => None,
+    }
+}
+
+fn is_call(node: &SyntaxNode, name: &str) -> bool {
+    match node.cast::<ast::FuncCall>() {
+        Some(call) => matches!(call.callee(), ast::Expr::Ident(ident) if ident.as_str() == name),
+        None => false,
+    }
+}
+
+/// Counts the text of markup, skipping code, math and raw text, whose output
+/// is unknown without compiling.
+fn count_markup(
+    node: &SyntaxNode,
+    in_markup: bool,
+    kind: TextKind,
+    words: &mut WordCounter,
+    statistics: &mut TypstTextStatistics,
+) {
+    let kind = syntax_kind(node).unwrap_or(kind);
+    let mut add = |words: &mut WordCounter, text: &str| {
+        add_count(kind_mut(statistics, kind), words.count(text));
+    };
+
+    match node.kind() {
+        SyntaxKind::Text | SyntaxKind::SmartQuote | SyntaxKind::Link => add(words, node.text()),
+        SyntaxKind::Escape => {
+            if let Some(escape) = node.cast::<ast::Escape>() {
+                add(words, escape.get().encode_utf8(&mut [0; 4]));
+            }
+        }
+        SyntaxKind::Shorthand => {
+            if let Some(shorthand) = node.cast::<ast::Shorthand>() {
+                add(words, shorthand.get().encode_utf8(&mut [0; 4]));
+            }
+        }
+        // A line break within a paragraph becomes a space
+        SyntaxKind::Space if in_markup => add(words, " "),
+        SyntaxKind::Star | SyntaxKind::Underscore => {}
+        SyntaxKind::Markup | SyntaxKind::Strong | SyntaxKind::Emph => {
+            let in_markup = node.kind() == SyntaxKind::Markup;
+            for child in node.children() {
+                count_markup(child, in_markup, kind, words, statistics);
+            }
+        }
+        SyntaxKind::Equation
+        | SyntaxKind::Raw
+        | SyntaxKind::LineComment
+        | SyntaxKind::BlockComment => words.split(),
+        _ => {
+            words.split();
+            for child in node.children() {
+                count_markup(child, false, kind, words, statistics);
+            }
+            words.split();
+        }
+    }
+}
+
+fn kind_mut(statistics: &mut TypstTextStatistics, kind: TextKind) -> &mut TypstTextCount {
+    match kind {
+        TextKind::Body => &mut statistics.body,
+        TextKind::Heading => &mut statistics.headings,
+        TextKind::Caption => &mut statistics.captions,
+        TextKind::Bibliography => &mut statistics.bibliography,
+    }
+}
+
+fn add_count(count: &mut TypstTextCount, other: TypstTextCount) {
+    count.words += other.words;
+    count.characters += other.characters;
+    count.characters_with_spaces += other.characters_with_spaces;
+}
+
+fn add_statistics(statistics: &mut TypstTextStatistics, other: &TypstTextStatistics) {
+    add_count(&mut statistics.body, other.body);
+    add_count(&mut statistics.headings, other.headings);
+    add_count(&mut statistics.captions, other.captions);
+    add_count(&mut statistics.bibliography, other.bibliography);
+}
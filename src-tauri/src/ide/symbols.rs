@@ -0,0 +1,180 @@
+use crate::ipc::TypstSymbol;
+use typst::foundations::Value;
+use typst::syntax::{LinkedNode, Source, SyntaxKind};
+use typst::World;
+
+/// Modules of the standard library which consist of symbols.
+const SYMBOL_MODULES: [&str; 2] = ["sym", "emoji"];
+
+enum Mode {
+    Markup,
+    Math,
+    Code,
+}
+
+/// Lists the variants of all symbols in the `sym` and `emoji` modules which
+/// match the query, best matches first.
+///
+/// A query consisting of a single non-ASCII character, such as a pasted glyph,
+/// matches the symbols with that character. Otherwise, each whitespace
+/// separated term of the query must be part of the path of the symbol.
+pub fn symbols(world: &dyn World, query: &str) -> Vec<TypstSymbol> {
+    let scope = world.library().global.scope();
+    let mut symbols = vec![];
+    for module_name in SYMBOL_MODULES {
+        let Some(Value::Module(module)) = scope.get(module_name) else {
+            continue;
+        };
+        for (name, value) in module.scope().iter() {
+            let Value::Symbol(symbol) = value else {
+                continue;
+            };
+            for (variant, c) in symbol.variants() {
+                let path = match variant {
+                    "" => format!("{}.{}", module_name, name),
+                    _ => format!("{}.{}.{}", module_name, name, variant),
+                };
+                symbols.push(TypstSymbol {
+                    path,
+                    module: module_name.to_string(),
+                    name: name.to_string(),
+                    variant: variant.to_string(),
+                    character: c,
+                    codepoint: c as u32,
+                });
+            }
+        }
+    }
+
+    // Emoji are often pasted with a variation selector
+    let query = query.trim().trim_end_matches('\u{fe0f}');
+    let mut chars = query.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        if !c.is_ascii() {
+            symbols.retain(|symbol| symbol.character == c);
+            return symbols;
+        }
+    }
+
+    // Matches ignore case, but symbols with the same case as the query, such as
+    // `sym.Alpha` for "Alpha", come before those which differ only in case
+    let lowercase = query.to_lowercase();
+    let lowercase_terms: Vec<&str> = lowercase.split_whitespace().collect();
+    let terms: Vec<&str> = query.split_whitespace().collect();
+    symbols.retain(|symbol| {
+        let path = symbol.path.to_lowercase();
+        lowercase_terms.iter().all(|term| path.contains(term))
+    });
+    symbols.sort_by_key(|symbol| {
+        let path = symbol.path.to_lowercase();
+        (
+            rank(symbol.module.len(), &path, &lowercase_terms),
+            rank(symbol.module.len(), &symbol.path, &terms),
+            symbol.path.len(),
+        )
+    });
+    symbols
+}
+
+/// Ranks symbols whose name and modifiers are exactly the terms first, e.g.
+/// `arrow.r` for "arrow r", followed by symbols which have a name or modifier
+/// equal to each term.
+fn rank(module_len: usize, path: &str, terms: &[&str]) -> u8 {
+    let parts: Vec<&str> = path[module_len + 1..].split('.').collect();
+    if parts == terms {
+        0
+    } else if terms.iter().all(|term| parts.contains(term)) {
+        1
+    } else {
+        2
+    }
+}
+
+/// Returns the text which inserts the symbol with the given path, e.g.
+/// `sym.arrow.r`, at the cursor. The symbol is written depending on whether
+/// the cursor is in markup, math or code.
+pub fn symbol_insertion(source: &Source, cursor: usize, path: &str) -> String {
+    // Characters which would otherwise continue the inserted path
+    let next = source
+        .text()
+        .get(cursor..)
+        .and_then(|text| text.chars().next());
+    let continues = next.is_some_and(|c| c.is_alphanumeric() || c == '_' || c == '.');
+
+    match mode_at(source, cursor) {
+        Mode::Code => path.to_string(),
+        // Symbols are in scope in math, but emoji are not
+        Mode::Math => match path.strip_prefix("sym.") {
+            Some(name) if continues => format!("{} ", name),
+            Some(name) => name.to_string(),
+            None if continues => format!("#{};", path),
+            None => format!("#{}", path),
+        },
+        Mode::Markup if continues => format!("#{};", path),
+        Mode::Markup => format!("#{}", path),
+    }
+}
+
+fn mode_at(source: &Source, cursor: usize) -> Mode {
+    let root = LinkedNode::new(source.root());
+    let mut node = root.leaf_at(cursor);
+    while let Some(current) = node {
+        // The delimiters of blocks and equations do not belong to their content
+        let inside = current.offset() < cursor && cursor < current.range().end;
+        match current.kind() {
+            SyntaxKind::Markup => return Mode::Markup,
+            SyntaxKind::Math => return Mode::Math,
+            SyntaxKind::Code => return Mode::Code,
+            SyntaxKind::ContentBlock if inside => return Mode::Markup,
+            SyntaxKind::Equation if inside => return Mode::Math,
+            SyntaxKind::CodeBlock
+            | SyntaxKind::Args
+            | SyntaxKind::Array
+            | SyntaxKind::Dict
+            | SyntaxKind::Parenthesized
+                if inside =>
+            {
+                return Mode::Code
+            }
+            _ => node = current.parent().cloned(),
+        }
+    }
+    Mode::Markup
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::ProjectWorld;
+
+    fn paths(query: &str) -> Vec<String> {
+        let dir = tempfile::tempdir().unwrap();
+        let world = ProjectWorld::new(dir.path().to_path_buf());
+        symbols(&world, query)
+            .into_iter()
+            .map(|symbol| symbol.path)
+            .collect()
+    }
+
+    #[test]
+    fn symbols_match_uppercase_query() {
+        let paths = paths("Alpha");
+        assert_eq!(paths.first().map(String::as_str), Some("sym.Alpha"));
+        assert!(paths.iter().any(|path| path == "sym.alpha"));
+    }
+
+    #[test]
+    fn symbols_match_lowercase_query() {
+        let paths = paths("alpha");
+        assert_eq!(paths.first().map(String::as_str), Some("sym.alpha"));
+        assert!(paths.iter().any(|path| path == "sym.Alpha"));
+    }
+
+    #[test]
+    fn symbols_match_multiple_terms() {
+        let uppercase = paths("arrow R");
+        assert_eq!(uppercase.first().map(String::as_str), Some("sym.arrow.R"));
+        let lowercase = paths("arrow r");
+        assert_eq!(lowercase.first().map(String::as_str), Some("sym.arrow.r"));
+    }
+}
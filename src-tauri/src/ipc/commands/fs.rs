@@ -1,13 +1,13 @@
 use super::{Error, Result};
 use crate::ipc::commands::project_path;
-use crate::project::ProjectManager;
+use crate::project::{Project, ProjectManager};
 use enumset::EnumSetType;
 use serde::Serialize;
 use std::cmp::Ordering;
 use std::fs;
 use std::fs::{File, OpenOptions};
 use std::io::Write;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tauri::{Runtime, State, Window};
 
@@ -89,13 +89,29 @@ pub async fn fs_write_file_text<R: Runtime>(
     content: String,
 ) -> Result<()> {
     let (project, absolute_path) = project_path(&window, &project_manager, &path)?;
+    write_file_text(&project, &path, &absolute_path, content)
+}
+
+/// Writes the content to the file and updates its slot in the project's world,
+/// so that the world is in sync without waiting for the file system event.
+pub fn write_file_text(
+    project: &Project,
+    path: &Path,
+    absolute_path: &Path,
+    content: String,
+) -> Result<()> {
     let _ = File::create(absolute_path)
         .map(|mut f| f.write_all(content.as_bytes()))
         .map_err(Into::<Error>::into)?;
 
+    // Pending changes are applied first, as they are older than the content
     let mut world = project.world.lock().unwrap();
+    project.compiler.flush(project, &mut world);
+    world
+        .slot_written(path, content.as_bytes())
+        .map_err(Into::<Error>::into)?;
     let _ = world
-        .slot_update(&path, Some(content))
+        .slot_update(path, Some(content))
         .map_err(Into::<Error>::into)?;
 
     Ok(())
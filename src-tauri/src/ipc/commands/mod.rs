@@ -6,6 +6,8 @@ pub use self::typst::*;
 pub use clipboard::*;
 pub use fs::*;
 
+use crate::export::ExportError;
+use crate::ide::RenameError;
 use crate::project::{Project, ProjectManager};
 use ::typst::diag::FileError;
 use serde::{Serialize, Serializer};
@@ -26,6 +28,10 @@ pub enum Error {
     TypstFile(#[from] FileError),
     #[error("the provided path does not belong to the project")]
     UnrelatedPath,
+    #[error(transparent)]
+    Rename(#[from] RenameError),
+    #[error(transparent)]
+    Export(#[from] ExportError),
 }
 
 impl Serialize for Error {
@@ -1,25 +1,47 @@
 use super::{Error, Result};
-use crate::ipc::commands::project;
-use crate::ipc::model::TypstRenderResponse;
-use crate::ipc::{
-    TypstCompileEvent, TypstDiagnosticSeverity, TypstDocument, TypstSourceDiagnostic,
+use crate::export;
+use crate::ide;
+use crate::ipc::commands::{project, project_path, write_file_text};
+use crate::ipc::model::{
+    TypstBibliographyEntry, TypstCodeAction, TypstDocs, TypstDocumentSymbol, TypstFoldingRange,
+    TypstJump, TypstLabel, TypstLocation, TypstOutlineItem, TypstPagePosition,
+    TypstPngExportOptions, TypstRenderResponse, TypstSemanticTokens, TypstSemanticTokensLegend,
+    TypstSourceEdit, TypstStatistics, TypstSvgExportOptions, TypstSymbol, TypstWorkspaceEdit,
 };
-use crate::project::ProjectManager;
+use crate::project::{project_statistics, ProjectManager, ProjectWorld, SourceChange};
 use base64::Engine;
 use log::debug;
 use serde::Serialize;
 use serde_repr::Serialize_repr;
-use siphasher::sip128::{Hasher128, SipHasher};
-use std::hash::Hash;
-use std::path::PathBuf;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::Instant;
 use tauri::Runtime;
-use typst::diag::Severity;
-use typst::eval::Tracer;
+use typst::layout::{Abs, Point, Position};
+use typst::syntax::{FileId, Source, VirtualPath};
 use typst::visualize::Color;
 use typst::World;
-use typst_ide::{Completion, CompletionKind};
+use typst_ide::{Completion, CompletionKind, Jump, Tooltip};
+
+/// Result ids of semantic tokens are shared between projects, like revisions.
+static SEMANTIC_TOKENS_RESULT: AtomicU64 = AtomicU64::new(0);
+
+/// Loads the source at `path`, replacing its content first if given. The
+/// content may be omitted if the source is kept up-to-date with `typst_edit`.
+fn updated_source(
+    world: &mut ProjectWorld,
+    path: &Path,
+    content: Option<String>,
+) -> Result<Source> {
+    let source_id = match content {
+        Some(content) => world.slot_update(path, Some(content)),
+        None => Ok(FileId::new(None, VirtualPath::new(path))),
+    }
+    .map_err(Into::<Error>::into)?;
+    world.source(source_id).map_err(Into::<Error>::into)
+}
 
 #[derive(Serialize_repr, Debug)]
 #[repr(u8)]
@@ -46,6 +68,22 @@ pub struct TypstCompleteResponse {
     completions: Vec<TypstCompletion>,
 }
 
+#[derive(Serialize, Debug)]
+#[serde(tag = "kind", content = "value", rename_all = "snake_case")]
+pub enum TypstTooltip {
+    Text(String),
+    Code(String),
+}
+
+impl From<Tooltip> for TypstTooltip {
+    fn from(value: Tooltip) -> Self {
+        match value {
+            Tooltip::Text(text) => Self::Text(text.to_string()),
+            Tooltip::Code(code) => Self::Code(code.to_string()),
+        }
+    }
+}
+
 impl From<Completion> for TypstCompletion {
     fn from(value: Completion) -> Self {
         Self {
@@ -64,112 +102,46 @@ impl From<Completion> for TypstCompletion {
     }
 }
 
+impl From<Position> for TypstPagePosition {
+    fn from(value: Position) -> Self {
+        Self {
+            page: value.page.get() - 1,
+            x: value.point.x.to_pt(),
+            y: value.point.y.to_pt(),
+        }
+    }
+}
+
+/// Queues a compilation of the project after updating the source at `path`.
+/// Returns the revision which will be reported in the resulting `typst_compile` event.
 #[tauri::command]
 pub async fn typst_compile<R: Runtime>(
     window: tauri::Window<R>,
     project_manager: tauri::State<'_, Arc<ProjectManager<R>>>,
     path: PathBuf,
     content: String,
-) -> Result<()> {
+) -> Result<u64> {
     let project = project(&window, &project_manager)?;
+    let change = SourceChange::Replace(content);
+    Ok(project
+        .compiler
+        .request(&project, &window, Some((path, change))))
+}
 
-    let mut world = project.world.lock().unwrap();
-    let source_id = world
-        .slot_update(&path, Some(content.clone()))
-        .map_err(Into::<Error>::into)?;
-
-    if !world.is_main_set() {
-        let config = project.config.read().unwrap();
-        if config.apply_main(&project, &mut world).is_err() {
-            debug!("skipped compilation for {:?} (main not set)", project);
-            return Ok(());
-        }
-    }
-
-    debug!("compiling {:?}: {:?}", path, project);
-    let now = Instant::now();
-    let mut tracer = Tracer::new();
-    match typst::compile(&*world, &mut tracer) {
-        Ok(doc) => {
-            let elapsed = now.elapsed();
-            debug!(
-                "compilation succeeded for {:?} in {:?} ms",
-                project,
-                elapsed.as_millis()
-            );
-
-            let pages = doc.pages.len();
-
-            let mut hasher = SipHasher::new();
-            for page in &doc.pages {
-                page.frame.hash(&mut hasher);
-            }
-            let hash = hex::encode(hasher.finish128().as_bytes());
-
-            // Assume all pages have the same size
-            // TODO: Improve this?
-            let first_page = &doc.pages[0];
-            let width = first_page.frame.width();
-            let height = first_page.frame.height();
-
-            project.cache.write().unwrap().document = Some(doc);
-
-            let _ = window.emit(
-                "typst_compile",
-                TypstCompileEvent {
-                    document: Some(TypstDocument {
-                        pages,
-                        hash,
-                        width: width.to_pt(),
-                        height: height.to_pt(),
-                    }),
-                    diagnostics: None,
-                },
-            );
-        }
-        Err(diagnostics) => {
-            debug!(
-                "compilation failed with {:?} diagnostics",
-                diagnostics.len()
-            );
-
-            let source = world.source(source_id);
-            let diagnostics: Vec<TypstSourceDiagnostic> = match source {
-                Ok(source) => diagnostics
-                    .iter()
-                    .filter(|d| d.span.id() == Some(source_id))
-                    .filter_map(|d| {
-                        let span = source.find(d.span)?;
-                        let range = span.range();
-                        let start = content[..range.start].chars().count();
-                        let size = content[range.start..range.end].chars().count();
-
-                        let message = d.message.to_string();
-                        Some(TypstSourceDiagnostic {
-                            range: start..start + size,
-                            severity: match d.severity {
-                                Severity::Error => TypstDiagnosticSeverity::Error,
-                                Severity::Warning => TypstDiagnosticSeverity::Warning,
-                            },
-                            message,
-                            hints: d.hints.iter().map(|hint| hint.to_string()).collect(),
-                        })
-                    })
-                    .collect(),
-                Err(_) => vec![],
-            };
-
-            let _ = window.emit(
-                "typst_compile",
-                TypstCompileEvent {
-                    document: None,
-                    diagnostics: Some(diagnostics),
-                },
-            );
-        }
-    }
-
-    Ok(())
+/// Applies edits to the source at `path` and queues a compilation, like
+/// `typst_compile`, without transferring the whole content.
+#[tauri::command]
+pub async fn typst_edit<R: Runtime>(
+    window: tauri::Window<R>,
+    project_manager: tauri::State<'_, Arc<ProjectManager<R>>>,
+    path: PathBuf,
+    edits: Vec<TypstSourceEdit>,
+) -> Result<u64> {
+    let project = project(&window, &project_manager)?;
+    let change = SourceChange::Edit(edits);
+    Ok(project
+        .compiler
+        .request(&project, &window, Some((path, change))))
 }
 
 #[tauri::command]
@@ -209,38 +181,436 @@ pub async fn typst_render<R: Runtime>(
     Err(Error::Unknown)
 }
 
+/// Renders pages of the last compiled document to PNG files in `directory`.
+/// Returns the paths of the written files.
+#[tauri::command]
+pub async fn typst_export_png<R: Runtime>(
+    window: tauri::Window<R>,
+    project_manager: tauri::State<'_, Arc<ProjectManager<R>>>,
+    directory: PathBuf,
+    options: TypstPngExportOptions,
+) -> Result<Vec<PathBuf>> {
+    let project = project(&window, &project_manager)?;
+    export::export_png(&project, &directory, &options).map_err(Into::<Error>::into)
+}
+
+/// Converts pages of the last compiled document to SVG files in `directory`.
+/// Returns the paths of the written files.
+#[tauri::command]
+pub async fn typst_export_svg<R: Runtime>(
+    window: tauri::Window<R>,
+    project_manager: tauri::State<'_, Arc<ProjectManager<R>>>,
+    directory: PathBuf,
+    options: TypstSvgExportOptions,
+) -> Result<Vec<PathBuf>> {
+    let project = project(&window, &project_manager)?;
+    export::export_svg(&project, &directory, &options).map_err(Into::<Error>::into)
+}
+
 #[tauri::command]
 pub async fn typst_autocomplete<R: Runtime>(
     window: tauri::Window<R>,
     project_manager: tauri::State<'_, Arc<ProjectManager<R>>>,
     path: PathBuf,
-    content: String,
+    content: Option<String>,
     offset: usize,
     explicit: bool,
 ) -> Result<TypstCompleteResponse> {
     let project = project(&window, &project_manager)?;
     let mut world = project.world.lock().unwrap();
-
-    let offset = content
-        .char_indices()
-        .nth(offset)
-        .map(|a| a.0)
-        .unwrap_or(content.len());
+    project.compiler.flush(&project, &mut world);
 
     // TODO: Improve error typing
-    let source_id = world
-        .slot_update(&*path, Some(content.clone()))
-        .map_err(Into::<Error>::into)?;
-
-    let source = world.source(source_id).map_err(Into::<Error>::into)?;
+    let source = updated_source(&mut world, &path, content)?;
+    let offset = source.utf16_to_byte(offset).unwrap_or(source.len_bytes());
 
+    // The cached document enables completions of labels and references
+    let cache = project.cache.read().unwrap();
     let (completed_offset, completions) =
-        typst_ide::autocomplete(&*world, None, &source, offset, explicit)
+        ide::autocomplete(&world, cache.document.as_ref(), &source, offset, explicit)
             .ok_or_else(|| Error::Unknown)?;
 
-    let completed_char_offset = content[..completed_offset].chars().count();
     Ok(TypstCompleteResponse {
-        offset: completed_char_offset,
+        offset: source.byte_to_utf16(completed_offset).unwrap_or_default(),
         completions: completions.into_iter().map(TypstCompletion::from).collect(),
     })
 }
+
+/// Resolves a click at (`x`, `y`) on the given page of the cached document, in
+/// points, to a location in the sources or a link target.
+#[tauri::command]
+pub async fn typst_jump_from_click<R: Runtime>(
+    window: tauri::Window<R>,
+    project_manager: tauri::State<'_, Arc<ProjectManager<R>>>,
+    page: usize,
+    x: f64,
+    y: f64,
+) -> Result<Option<TypstJump>> {
+    let project = project(&window, &project_manager)?;
+    let world = project.world.lock().unwrap();
+    let cache = project.cache.read().unwrap();
+
+    let doc = match &cache.document {
+        Some(doc) => doc,
+        None => return Ok(None),
+    };
+    let frame = &doc.pages.get(page).ok_or(Error::Unknown)?.frame;
+    let click = Point::new(Abs::pt(x), Abs::pt(y));
+
+    let jump = match typst_ide::jump_from_click(&*world, doc, frame, click) {
+        Some(Jump::Source(id, offset)) => {
+            let source = world.source(id).map_err(Into::<Error>::into)?;
+            Some(TypstJump::Source {
+                path: id.vpath().as_rooted_path().to_path_buf(),
+                package: id.package().map(|spec| spec.to_string()),
+                offset: source.byte_to_utf16(offset).unwrap_or_default(),
+            })
+        }
+        Some(Jump::Url(url)) => Some(TypstJump::Url {
+            url: url.to_string(),
+        }),
+        Some(Jump::Position(position)) => Some(TypstJump::Position(position.into())),
+        None => None,
+    };
+
+    Ok(jump)
+}
+
+/// Resolves a UTF-16 offset in the source at `path` to its position in the
+/// cached document, so that the preview can reveal it.
+#[tauri::command]
+pub async fn typst_jump_from_cursor<R: Runtime>(
+    window: tauri::Window<R>,
+    project_manager: tauri::State<'_, Arc<ProjectManager<R>>>,
+    path: PathBuf,
+    offset: usize,
+) -> Result<Option<TypstPagePosition>> {
+    let project = project(&window, &project_manager)?;
+    let mut world = project.world.lock().unwrap();
+    project.compiler.flush(&project, &mut world);
+    let cache = project.cache.read().unwrap();
+
+    let doc = match &cache.document {
+        Some(doc) => doc,
+        None => return Ok(None),
+    };
+    let source = world
+        .source(FileId::new(None, VirtualPath::new(&path)))
+        .map_err(Into::<Error>::into)?;
+
+    let offset = source.utf16_to_byte(offset).unwrap_or(source.len_bytes());
+
+    Ok(typst_ide::jump_from_cursor(doc, &source, offset).map(Into::into))
+}
+
+#[tauri::command]
+pub async fn typst_tooltip<R: Runtime>(
+    window: tauri::Window<R>,
+    project_manager: tauri::State<'_, Arc<ProjectManager<R>>>,
+    path: PathBuf,
+    content: Option<String>,
+    offset: usize,
+) -> Result<Option<TypstTooltip>> {
+    let project = project(&window, &project_manager)?;
+    let mut world = project.world.lock().unwrap();
+    project.compiler.flush(&project, &mut world);
+    let source = updated_source(&mut world, &path, content)?;
+
+    let offset = source.utf16_to_byte(offset).unwrap_or(source.len_bytes());
+
+    // The cached document enables tooltips for labels and references
+    let cache = project.cache.read().unwrap();
+    let tooltip = typst_ide::tooltip(&*world, cache.document.as_ref(), &source, offset);
+
+    Ok(tooltip.map(Into::into))
+}
+
+/// Finds the definition of the identifier, import path or label reference at
+/// the UTF-16 `offset`, possibly in another file or package.
+#[tauri::command]
+pub async fn typst_definition<R: Runtime>(
+    window: tauri::Window<R>,
+    project_manager: tauri::State<'_, Arc<ProjectManager<R>>>,
+    path: PathBuf,
+    content: Option<String>,
+    offset: usize,
+) -> Result<Option<TypstLocation>> {
+    let project = project(&window, &project_manager)?;
+    let mut world = project.world.lock().unwrap();
+    project.compiler.flush(&project, &mut world);
+    let source = updated_source(&mut world, &path, content)?;
+
+    let offset = source.utf16_to_byte(offset).unwrap_or(source.len_bytes());
+
+    let cache = project.cache.read().unwrap();
+    let location = ide::definition(&world, cache.document.as_ref(), &source, offset)
+        .and_then(|definition| ide::location(&*world, definition.id, definition.range));
+
+    Ok(location)
+}
+
+/// Builds the outline of the cached document. The outline is also emitted as
+/// the `typst_outline` event after every successful compilation.
+#[tauri::command]
+pub async fn typst_outline<R: Runtime>(
+    window: tauri::Window<R>,
+    project_manager: tauri::State<'_, Arc<ProjectManager<R>>>,
+) -> Result<Vec<TypstOutlineItem>> {
+    let project = project(&window, &project_manager)?;
+    let world = project.world.lock().unwrap();
+    let cache = project.cache.read().unwrap();
+
+    Ok(cache
+        .document
+        .as_ref()
+        .map(|doc| ide::outline(&*world, doc))
+        .unwrap_or_default())
+}
+
+/// Computes word and character statistics of the cached document and the
+/// sources of the project. The statistics are also emitted as the
+/// `typst_statistics` event after every successful compilation.
+#[tauri::command]
+pub async fn typst_statistics<R: Runtime>(
+    window: tauri::Window<R>,
+    project_manager: tauri::State<'_, Arc<ProjectManager<R>>>,
+) -> Result<TypstStatistics> {
+    let project = project(&window, &project_manager)?;
+    project
+        .compiler
+        .flush(&project, &mut project.world.lock().unwrap());
+
+    Ok(project_statistics(&project))
+}
+
+/// Lists the symbols of the `sym` and `emoji` modules which match the query,
+/// either by a fragment of their path or by their character.
+#[tauri::command]
+pub async fn typst_symbols<R: Runtime>(
+    window: tauri::Window<R>,
+    project_manager: tauri::State<'_, Arc<ProjectManager<R>>>,
+    query: Option<String>,
+) -> Result<Vec<TypstSymbol>> {
+    let project = project(&window, &project_manager)?;
+    let world = project.world.lock().unwrap();
+
+    Ok(ide::symbols(&*world, query.as_deref().unwrap_or_default()))
+}
+
+/// Returns the text which inserts the symbol at `symbol`, e.g. `sym.arrow.r`,
+/// at the UTF-16 `offset`, depending on whether it is in markup, math or code.
+#[tauri::command]
+pub async fn typst_symbol_insertion<R: Runtime>(
+    window: tauri::Window<R>,
+    project_manager: tauri::State<'_, Arc<ProjectManager<R>>>,
+    path: PathBuf,
+    content: Option<String>,
+    offset: usize,
+    symbol: String,
+) -> Result<String> {
+    let project = project(&window, &project_manager)?;
+    let mut world = project.world.lock().unwrap();
+    project.compiler.flush(&project, &mut world);
+    let source = updated_source(&mut world, &path, content)?;
+
+    let offset = source.utf16_to_byte(offset).unwrap_or(source.len_bytes());
+
+    Ok(ide::symbol_insertion(&source, offset, &symbol))
+}
+
+/// Documents the function, type or module of the standard library at the
+/// dotted path `name`, e.g. `calc.pow`. Without a name, the global scope is
+/// documented.
+#[tauri::command]
+pub async fn typst_docs<R: Runtime>(
+    window: tauri::Window<R>,
+    project_manager: tauri::State<'_, Arc<ProjectManager<R>>>,
+    name: Option<String>,
+) -> Result<Option<TypstDocs>> {
+    let project = project(&window, &project_manager)?;
+    let world = project.world.lock().unwrap();
+
+    Ok(ide::docs(&*world, name.as_deref().unwrap_or_default()))
+}
+
+/// Returns the token types and modifiers which semantic tokens refer to.
+#[tauri::command]
+pub async fn typst_semantic_tokens_legend() -> TypstSemanticTokensLegend {
+    TypstSemanticTokensLegend {
+        token_types: ide::TOKEN_TYPES.iter().map(|ty| ty.to_string()).collect(),
+        token_modifiers: ide::TOKEN_MODIFIERS
+            .iter()
+            .map(|modifier| modifier.to_string())
+            .collect(),
+    }
+}
+
+/// Computes the semantic tokens of the source at `path` from its syntax tree.
+/// If `previous_result_id` identifies the tokens last returned for the source,
+/// only the edits to them are returned.
+#[tauri::command]
+pub async fn typst_semantic_tokens<R: Runtime>(
+    window: tauri::Window<R>,
+    project_manager: tauri::State<'_, Arc<ProjectManager<R>>>,
+    path: PathBuf,
+    content: Option<String>,
+    previous_result_id: Option<String>,
+) -> Result<TypstSemanticTokens> {
+    let project = project(&window, &project_manager)?;
+    let mut world = project.world.lock().unwrap();
+    project.compiler.flush(&project, &mut world);
+    let source = updated_source(&mut world, &path, content)?;
+
+    let data = ide::semantic_tokens(&source);
+    let result_id = (SEMANTIC_TOKENS_RESULT.fetch_add(1, Ordering::Relaxed) + 1).to_string();
+
+    let mut cache = project.cache.write().unwrap();
+    let tokens = match cache.semantic_tokens.get(&source.id()) {
+        Some((id, previous)) if previous_result_id.as_ref() == Some(id) => {
+            TypstSemanticTokens::Delta {
+                result_id: result_id.clone(),
+                edits: ide::semantic_token_edits(previous, &data),
+            }
+        }
+        _ => TypstSemanticTokens::Full {
+            result_id: result_id.clone(),
+            data: data.clone(),
+        },
+    };
+    cache.semantic_tokens.insert(source.id(), (result_id, data));
+
+    Ok(tokens)
+}
+
+/// Finds the foldable regions of the source at `path` from its syntax tree.
+#[tauri::command]
+pub async fn typst_folding_ranges<R: Runtime>(
+    window: tauri::Window<R>,
+    project_manager: tauri::State<'_, Arc<ProjectManager<R>>>,
+    path: PathBuf,
+    content: Option<String>,
+) -> Result<Vec<TypstFoldingRange>> {
+    let project = project(&window, &project_manager)?;
+    let mut world = project.world.lock().unwrap();
+    project.compiler.flush(&project, &mut world);
+    let source = updated_source(&mut world, &path, content)?;
+
+    Ok(ide::folding_ranges(&source))
+}
+
+/// Lists the headings, bindings, show rules and labels of the source at `path`
+/// from its syntax tree.
+#[tauri::command]
+pub async fn typst_document_symbols<R: Runtime>(
+    window: tauri::Window<R>,
+    project_manager: tauri::State<'_, Arc<ProjectManager<R>>>,
+    path: PathBuf,
+    content: Option<String>,
+) -> Result<Vec<TypstDocumentSymbol>> {
+    let project = project(&window, &project_manager)?;
+    let mut world = project.world.lock().unwrap();
+    project.compiler.flush(&project, &mut world);
+    let source = updated_source(&mut world, &path, content)?;
+
+    Ok(ide::document_symbols(&source))
+}
+
+/// Derives quick fixes for the diagnostics of the last compilation which
+/// overlap the character `range` of the source at `path`.
+#[tauri::command]
+pub async fn typst_code_actions<R: Runtime>(
+    window: tauri::Window<R>,
+    project_manager: tauri::State<'_, Arc<ProjectManager<R>>>,
+    path: PathBuf,
+    content: Option<String>,
+    range: Range<usize>,
+) -> Result<Vec<TypstCodeAction>> {
+    let project = project(&window, &project_manager)?;
+    let mut world = project.world.lock().unwrap();
+    project.compiler.flush(&project, &mut world);
+    let source = updated_source(&mut world, &path, content)?;
+
+    let to_byte = |offset: usize| source.utf16_to_byte(offset).unwrap_or(source.len_bytes());
+    let range = to_byte(range.start)..to_byte(range.end);
+
+    let cache = project.cache.read().unwrap();
+    Ok(ide::code_actions(
+        &world,
+        &source,
+        range,
+        &cache.diagnostics,
+    ))
+}
+
+/// Indexes the labels and references of all sources in the project, and flags
+/// duplicate, unused and broken labels.
+#[tauri::command]
+pub async fn typst_labels<R: Runtime>(
+    window: tauri::Window<R>,
+    project_manager: tauri::State<'_, Arc<ProjectManager<R>>>,
+) -> Result<Vec<TypstLabel>> {
+    let project = project(&window, &project_manager)?;
+    let world = project.world.lock().unwrap();
+    let cache = project.cache.read().unwrap();
+
+    Ok(ide::labels(&world, cache.document.as_ref()))
+}
+
+/// Returns the entry for `key` from the bibliography files of the project.
+#[tauri::command]
+pub async fn typst_bibliography_entry<R: Runtime>(
+    window: tauri::Window<R>,
+    project_manager: tauri::State<'_, Arc<ProjectManager<R>>>,
+    key: String,
+) -> Result<Option<TypstBibliographyEntry>> {
+    let project = project(&window, &project_manager)?;
+    let world = project.world.lock().unwrap();
+
+    Ok(ide::bibliography_entry(&world, &key))
+}
+
+/// Computes the edits which rename the label or binding at the character
+/// `offset` to `new_name` across the project. The edits are not applied, so
+/// that they can be previewed.
+#[tauri::command]
+pub async fn typst_rename<R: Runtime>(
+    window: tauri::Window<R>,
+    project_manager: tauri::State<'_, Arc<ProjectManager<R>>>,
+    path: PathBuf,
+    content: Option<String>,
+    offset: usize,
+    new_name: String,
+) -> Result<TypstWorkspaceEdit> {
+    let project = project(&window, &project_manager)?;
+    let mut world = project.world.lock().unwrap();
+    project.compiler.flush(&project, &mut world);
+    let source = updated_source(&mut world, &path, content)?;
+
+    let offset = source.utf16_to_byte(offset).unwrap_or(source.len_bytes());
+
+    ide::rename(&world, &source, offset, &new_name).map_err(Into::<Error>::into)
+}
+
+/// Applies a workspace edit to the sources in the project's world, and writes
+/// the edited files like `fs_write_file_text`.
+#[tauri::command]
+pub async fn typst_apply_edit<R: Runtime>(
+    window: tauri::Window<R>,
+    project_manager: tauri::State<'_, Arc<ProjectManager<R>>>,
+    edit: TypstWorkspaceEdit,
+) -> Result<()> {
+    for file in edit.files {
+        let (project, absolute_path) = project_path(&window, &project_manager, &file.path)?;
+        let content = {
+            let mut world = project.world.lock().unwrap();
+            project.compiler.flush(&project, &mut world);
+            let source = world
+                .source(FileId::new(None, VirtualPath::new(&file.path)))
+                .map_err(Into::<Error>::into)?;
+            ide::apply_edits(&source, &file.edits)
+        };
+        write_file_text(&project, &file.path, &absolute_path, content)?;
+    }
+
+    Ok(())
+}
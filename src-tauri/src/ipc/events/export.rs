@@ -0,0 +1,15 @@
+use serde::Serialize;
+use tauri::{Runtime, Window};
+
+#[derive(Debug, Clone, Serialize)]
+struct EmptyPayload {}
+
+// Instructs the front-end to ask for the export options, which it passes to `typst_export_png`
+pub fn request_png_export<R: Runtime>(window: &Window<R>) {
+    let _ = window.emit("export_png", EmptyPayload {});
+}
+
+// Instructs the front-end to ask for the export options, which it passes to `typst_export_svg`
+pub fn request_svg_export<R: Runtime>(window: &Window<R>) {
+    let _ = window.emit("export_svg", EmptyPayload {});
+}
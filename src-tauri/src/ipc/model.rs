@@ -1,16 +1,34 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use std::ops::Range;
 use std::path::PathBuf;
 
 #[derive(Serialize, Clone, Debug)]
 pub struct TypstCompileEvent {
+    /// Revision of the compile request this event belongs to. Revisions increase
+    /// monotonically, even across projects.
+    pub revision: u64,
     pub document: Option<TypstDocument>,
     pub diagnostics: Option<Vec<TypstSourceDiagnostic>>,
+    /// Diagnostics whose span could not be resolved to a source file.
+    pub detached_diagnostics: Option<Vec<TypstDiagnostic>>,
 }
 
 #[derive(Serialize, Clone, Debug)]
 pub struct TypstDocument {
-    pub pages: usize,
+    pub pages: Vec<TypstPage>,
+    pub hash: String,
+    /// Indices of the pages whose content has changed since the previously
+    /// emitted document. Pages which did not exist before are included.
+    pub changed_pages: Vec<usize>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct TypstPage {
+    /// The logical page number, which is controlled by `counter(page)` and may
+    /// therefore differ from the physical page index.
+    pub number: usize,
+    /// The page number formatted with the page's numbering, if it has one.
+    pub label: Option<String>,
     pub hash: String,
     pub width: f64,
     pub height: f64,
@@ -25,12 +43,24 @@ pub enum TypstDiagnosticSeverity {
 
 #[derive(Serialize, Clone, Debug)]
 pub struct TypstSourceDiagnostic {
+    /// Path of the source, relative to the project root or the package root.
+    pub path: PathBuf,
+    /// Package specification, e.g. `@preview/example:0.1.0`, if the source
+    /// belongs to a package.
+    pub package: Option<String>,
     pub range: Range<usize>,
     pub severity: TypstDiagnosticSeverity,
     pub message: String,
     pub hints: Vec<String>,
 }
 
+#[derive(Serialize, Clone, Debug)]
+pub struct TypstDiagnostic {
+    pub severity: TypstDiagnosticSeverity,
+    pub message: String,
+    pub hints: Vec<String>,
+}
+
 #[derive(Serialize, Clone, Debug)]
 pub struct TypstRenderResponse {
     pub image: String,
@@ -39,6 +69,355 @@ pub struct TypstRenderResponse {
     pub nonce: u32,
 }
 
+/// A location to jump to, either in the sources or in the preview.
+#[derive(Serialize, Clone, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TypstJump {
+    /// An offset in UTF-16 code units within a source file.
+    Source {
+        path: PathBuf,
+        package: Option<String>,
+        offset: usize,
+    },
+    /// An external URL.
+    Url { url: String },
+    /// A point in the document.
+    Position(TypstPagePosition),
+}
+
+/// A point on a page, in points. The page is a zero-based index.
+#[derive(Serialize, Clone, Debug)]
+pub struct TypstPagePosition {
+    pub page: usize,
+    pub x: f64,
+    pub y: f64,
+}
+
+/// A range in UTF-16 code units within a project or package source.
+#[derive(Serialize, Clone, Debug)]
+pub struct TypstLocation {
+    pub path: PathBuf,
+    pub package: Option<String>,
+    pub range: Range<usize>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct TypstOutlineEvent {
+    /// Revision of the compilation the outline was built from.
+    pub revision: u64,
+    pub items: Vec<TypstOutlineItem>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum TypstOutlineKind {
+    Heading,
+    Figure,
+    Table,
+    Equation,
+}
+
+/// An entry of the document outline. Figures, tables and equations are nested
+/// within the heading they follow.
+#[derive(Serialize, Clone, Debug)]
+pub struct TypstOutlineItem {
+    pub kind: TypstOutlineKind,
+    pub title: String,
+    /// The heading level, or the depth within the outline for other entries.
+    pub level: usize,
+    /// The number formatted with the element's numbering, if it is numbered.
+    pub numbering: Option<String>,
+    pub label: Option<String>,
+    pub position: TypstPagePosition,
+    /// The source the element was created from, if it is known.
+    pub location: Option<TypstLocation>,
+    pub children: Vec<TypstOutlineItem>,
+}
+
+/// A label of the project, with the places it is defined and referenced in.
+#[derive(Serialize, Clone, Debug)]
+pub struct TypstLabel {
+    pub name: String,
+    /// Labels attached to content, e.g. `= Introduction <intro>`.
+    pub definitions: Vec<TypstLocation>,
+    /// References, e.g. `@intro`, and labels passed as arguments, e.g. `ref(<intro>)`.
+    pub references: Vec<TypstLocation>,
+    /// The label is defined more than once.
+    pub duplicate: bool,
+    /// The label is defined, but never referenced.
+    pub unused: bool,
+    /// The label is referenced, but neither defined in the sources nor known
+    /// to the last compiled document, e.g. as a bibliography key.
+    pub broken: bool,
+}
+
+/// An entry of a bibliography file referenced by the project.
+#[derive(Serialize, Clone, Debug)]
+pub struct TypstBibliographyEntry {
+    pub key: String,
+    pub title: Option<String>,
+    pub authors: Vec<String>,
+    pub year: Option<i32>,
+    /// The title of the containing work, e.g. a journal or proceedings.
+    pub container: Option<String>,
+    /// Path of the bibliography file, relative to the project root.
+    pub path: PathBuf,
+}
+
+/// Text edits to several files of the project.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TypstWorkspaceEdit {
+    pub files: Vec<TypstFileEdit>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TypstFileEdit {
+    /// Path of the file, relative to the project root.
+    pub path: PathBuf,
+    /// Non-overlapping edits, ordered by their position.
+    pub edits: Vec<TypstTextEdit>,
+}
+
+/// Replaces a range in UTF-16 code units with `text`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TypstTextEdit {
+    pub range: Range<usize>,
+    pub text: String,
+}
+
+/// Replaces a range of an open source with `text`. Unlike [TypstTextEdit], the
+/// range is in UTF-16 code units, as reported by the editor.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TypstSourceEdit {
+    pub range: Range<usize>,
+    pub text: String,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct TypstStatisticsEvent {
+    /// Revision of the compilation the statistics were computed from.
+    pub revision: u64,
+    pub statistics: TypstStatistics,
+}
+
+/// Word and character counts of the project. Counts are split by the kind of
+/// text, so that headings, captions and the bibliography can be excluded.
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct TypstStatistics {
+    /// Counts of the compiled document.
+    pub document: TypstTextStatistics,
+    /// Counts of the compiled document by page.
+    pub pages: Vec<TypstTextStatistics>,
+    /// Counts of the compiled document by heading section.
+    pub sections: Vec<TypstSectionStatistics>,
+    /// Counts of the markup in the source files. The bibliography is not
+    /// part of the sources, so it is never counted here.
+    pub files: Vec<TypstFileStatistics>,
+}
+
+#[derive(Serialize, Clone, Copy, Debug, Default)]
+pub struct TypstTextStatistics {
+    pub body: TypstTextCount,
+    pub headings: TypstTextCount,
+    pub captions: TypstTextCount,
+    pub bibliography: TypstTextCount,
+}
+
+#[derive(Serialize, Clone, Copy, Debug, Default)]
+pub struct TypstTextCount {
+    pub words: usize,
+    /// Characters other than whitespace.
+    pub characters: usize,
+    /// Characters including spaces within lines.
+    pub characters_with_spaces: usize,
+}
+
+/// The text from a heading up to the next heading of any level.
+#[derive(Serialize, Clone, Debug)]
+pub struct TypstSectionStatistics {
+    /// The title of the heading, or [Option::None] for the text before the
+    /// first heading.
+    pub title: Option<String>,
+    pub level: usize,
+    pub location: Option<TypstLocation>,
+    pub statistics: TypstTextStatistics,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct TypstFileStatistics {
+    /// Path of the file, relative to the project root.
+    pub path: PathBuf,
+    pub statistics: TypstTextStatistics,
+}
+
+/// A variant of a symbol from the `sym` or `emoji` module.
+#[derive(Serialize, Clone, Debug)]
+pub struct TypstSymbol {
+    /// The full path of the variant, e.g. `sym.arrow.r.double`.
+    pub path: String,
+    /// Either `sym` or `emoji`.
+    pub module: String,
+    /// The name of the symbol, e.g. `arrow`.
+    pub name: String,
+    /// The modifiers of the variant, e.g. `r.double`. Empty for the default variant.
+    pub variant: String,
+    pub character: char,
+    pub codepoint: u32,
+}
+
+/// Documentation of a function, type or module of the standard library.
+#[derive(Serialize, Clone, Debug)]
+pub struct TypstDocs {
+    /// The dotted path of the item, e.g. `calc.pow`.
+    pub path: String,
+    pub kind: TypstDocsKind,
+    pub title: String,
+    /// The documentation in Markdown.
+    pub docs: String,
+    /// Parameters of the function, or of the constructor of a type.
+    pub params: Vec<TypstParam>,
+    /// Types returned by the function.
+    pub returns: Vec<String>,
+    /// Items in the scope of the function, type or module, e.g. methods.
+    pub members: Vec<TypstDocsMember>,
+}
+
+#[derive(Serialize, Clone, Copy, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum TypstDocsKind {
+    Function,
+    Type,
+    Module,
+    Value,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct TypstDocsMember {
+    pub name: String,
+    pub kind: TypstDocsKind,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct TypstParam {
+    pub name: String,
+    pub docs: String,
+    /// Accepted types and values, e.g. `["auto", "length"]`.
+    pub types: Vec<String>,
+    /// The default value, in Typst syntax.
+    pub default: Option<String>,
+    pub positional: bool,
+    pub named: bool,
+    pub variadic: bool,
+    pub required: bool,
+    /// The parameter can be set with a set rule.
+    pub settable: bool,
+}
+
+/// Semantic tokens of a source, encoded as in the Language Server Protocol.
+#[derive(Serialize, Clone, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TypstSemanticTokens {
+    Full {
+        result_id: String,
+        data: Vec<u32>,
+    },
+    /// Edits to the tokens of the previous result.
+    Delta {
+        result_id: String,
+        edits: Vec<TypstSemanticTokensEdit>,
+    },
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct TypstSemanticTokensEdit {
+    /// Index of the first integer to replace.
+    pub start: usize,
+    pub delete_count: usize,
+    pub data: Vec<u32>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct TypstSemanticTokensLegend {
+    pub token_types: Vec<String>,
+    pub token_modifiers: Vec<String>,
+}
+
+/// A foldable range of lines, which are zero-based and inclusive.
+#[derive(Serialize, Clone, Debug)]
+pub struct TypstFoldingRange {
+    pub start_line: usize,
+    pub end_line: usize,
+    pub kind: Option<TypstFoldingKind>,
+}
+
+#[derive(Serialize, Clone, Copy, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum TypstFoldingKind {
+    Comment,
+}
+
+/// A symbol of a source, with ranges in UTF-16 code units.
+#[derive(Serialize, Clone, Debug)]
+pub struct TypstDocumentSymbol {
+    pub name: String,
+    /// The parameters of functions, or the transformation of show rules.
+    pub detail: Option<String>,
+    pub kind: TypstDocumentSymbolKind,
+    /// The range of the whole symbol, e.g. a heading and its section.
+    pub range: Range<usize>,
+    /// The range to select when navigating to the symbol, e.g. its name.
+    pub selection_range: Range<usize>,
+    pub children: Vec<TypstDocumentSymbol>,
+}
+
+#[derive(Serialize, Clone, Copy, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum TypstDocumentSymbolKind {
+    Heading,
+    Variable,
+    Function,
+    ShowRule,
+    Label,
+}
+
+/// A fix for a diagnostic which can be applied without further input.
+#[derive(Serialize, Clone, Debug)]
+pub struct TypstCodeAction {
+    pub title: String,
+    /// Message of the diagnostic which is fixed.
+    pub message: String,
+    /// UTF-16 range of the diagnostic which is fixed.
+    pub range: Range<usize>,
+    /// A file to create before applying the edit, relative to the project root.
+    pub create_file: Option<PathBuf>,
+    pub edit: TypstWorkspaceEdit,
+}
+
+/// Options for exporting pages of the document as PNG images.
+#[derive(Deserialize, Clone, Debug)]
+pub struct TypstPngExportOptions {
+    /// One-based number of the first page to export, defaulting to the first page.
+    pub first_page: Option<usize>,
+    /// One-based number of the last page to export, defaulting to the last page.
+    pub last_page: Option<usize>,
+    /// Resolution of the images in pixels per inch.
+    pub dpi: f32,
+    /// Name of the files, in which `{name}` is replaced by the name of the main
+    /// source and `{page}` by the page number. The extension is appended if it
+    /// is missing.
+    pub pattern: String,
+}
+
+/// Options for exporting pages of the document as SVG images.
+#[derive(Deserialize, Clone, Debug)]
+pub struct TypstSvgExportOptions {
+    /// One-based number of the page to export, or [Option::None] to export
+    /// each page to its own file.
+    pub page: Option<usize>,
+    /// Name of the files, as in [TypstPngExportOptions].
+    pub pattern: String,
+}
+
 #[derive(Serialize, Clone, Debug)]
 pub struct ProjectChangeEvent {
     pub project: Option<ProjectModel>,
@@ -53,3 +432,10 @@ pub struct ProjectModel {
 pub struct FSRefreshEvent {
     pub path: PathBuf,
 }
+
+/// A source which was reloaded after it changed on disk.
+#[derive(Serialize, Clone, Debug)]
+pub struct FSReloadEvent {
+    /// Path of the source, relative to the project root.
+    pub path: PathBuf,
+}
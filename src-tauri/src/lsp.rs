@@ -0,0 +1,595 @@
+use crate::ide;
+use crate::ipc::{TypstDiagnosticSeverity, TypstSourceDiagnostic, TypstSourceEdit};
+use crate::project::{compile, is_project_config_file, Project, ProjectConfig, ProjectWorld};
+use log::{debug, error, warn};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Sender};
+use std::sync::Arc;
+use std::thread;
+use typst::syntax::{FileId, Source, VirtualPath};
+use typst::World;
+use typst_ide::{CompletionKind, Tooltip};
+use url::Url;
+
+/// Sent after every successful compilation, with the compiled document and
+/// the pages which have changed, so that clients can refresh their preview.
+const NOTIFICATION_PREVIEW_INVALIDATED: &str = "typstudio/previewInvalidated";
+
+/// Identifies the request which registers the file watcher, the only request
+/// sent by the server.
+const REQUEST_WATCH_FILES: &str = "typstudio/watchFiles";
+
+const ERROR_METHOD_NOT_FOUND: i64 = -32601;
+const ERROR_INVALID_PARAMS: i64 = -32602;
+const ERROR_SERVER_NOT_INITIALIZED: i64 = -32002;
+
+type ResponseError = (i64, String);
+
+#[derive(Default)]
+struct Server {
+    project: Option<Arc<Project>>,
+    /// Queues compilations on the compiler thread, which is started upon
+    /// initialization.
+    compiler: Option<Sender<()>>,
+    /// Whether the client can register file watchers for the server.
+    watch_files: bool,
+    shutdown: bool,
+}
+
+/// Compiles the project on its own thread and publishes the results, so that
+/// requests are answered while compiling.
+struct Compiler {
+    project: Arc<Project>,
+    /// Paths of the files which have diagnostics published for them.
+    published: Vec<PathBuf>,
+    revision: u64,
+}
+
+/// Serves the Language Server Protocol over stdio until the client exits.
+/// Returns the exit code.
+pub fn run() -> i32 {
+    let stdin = io::stdin();
+    let mut reader = BufReader::new(stdin.lock());
+    let mut server = Server::default();
+    let mut dirty = false;
+
+    loop {
+        let message = match read_message(&mut reader) {
+            Ok(Some(message)) => message,
+            Ok(None) => return 1,
+            Err(e) => {
+                error!("unable to read message: {:?}", e);
+                return 1;
+            }
+        };
+
+        let method = message["method"].as_str().unwrap_or_default();
+        let params = &message["params"];
+        match message.get("id") {
+            // Responses to requests of the server are only logged
+            Some(id) if method.is_empty() => {
+                if let Some(error) = message.get("error") {
+                    warn!("request {} failed: {}", id, error);
+                }
+            }
+            Some(id) => {
+                let response = match server.handle_request(method, params) {
+                    Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+                    Err((code, message)) => json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "error": { "code": code, "message": message },
+                    }),
+                };
+                send(&response);
+            }
+            None if method == "exit" => return if server.shutdown { 0 } else { 1 },
+            None => dirty |= server.handle_notification(method, params),
+        }
+
+        // Compiles once the messages which have already arrived are handled,
+        // rather than after every single change
+        if dirty && reader.buffer().is_empty() {
+            server.compile();
+            dirty = false;
+        }
+    }
+}
+
+impl Compiler {
+    fn spawn(project: Arc<Project>) -> Sender<()> {
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let mut compiler = Compiler {
+                project,
+                published: vec![],
+                revision: 0,
+            };
+            while receiver.recv().is_ok() {
+                // Requests which arrived while compiling are handled at once
+                while receiver.try_recv().is_ok() {}
+                compiler.compile();
+            }
+        });
+        sender
+    }
+
+    fn compile(&mut self) {
+        let project = &self.project;
+        self.revision += 1;
+
+        // A snapshot is compiled, so that requests can use the world meanwhile
+        let mut world = project.world.lock().unwrap().snapshot();
+        let output = compile(project, &mut world, self.revision);
+        project
+            .world
+            .lock()
+            .unwrap()
+            .merge_snapshot(world.snapshot());
+        let Some(mut output) = output else {
+            return;
+        };
+
+        let mut diagnostics: HashMap<PathBuf, Vec<Value>> = HashMap::new();
+        for diagnostic in output.event.diagnostics.iter().flatten() {
+            // Diagnostics within packages cannot be shown by the client
+            if diagnostic.package.is_some() {
+                continue;
+            }
+            if let Some(value) = map_diagnostic(&world, diagnostic) {
+                diagnostics
+                    .entry(diagnostic.path.clone())
+                    .or_default()
+                    .push(value);
+            }
+        }
+
+        for diagnostic in output.event.detached_diagnostics.iter().flatten() {
+            let kind = match diagnostic.severity {
+                TypstDiagnosticSeverity::Error => 1,
+                TypstDiagnosticSeverity::Warning => 2,
+            };
+            notify(
+                "window/logMessage",
+                json!({ "type": kind, "message": diagnostic.message }),
+            );
+        }
+
+        // Clears the diagnostics of files which no longer have any
+        for path in self.published.drain(..) {
+            diagnostics.entry(path).or_default();
+        }
+        for (path, values) in diagnostics {
+            let Some(uri) = file_uri(&project.root, &path) else {
+                continue;
+            };
+            if !values.is_empty() {
+                self.published.push(path);
+            }
+            notify(
+                "textDocument/publishDiagnostics",
+                json!({ "uri": uri, "diagnostics": values }),
+            );
+        }
+
+        output.store(project);
+        if let Some(document) = &output.event.document {
+            notify(
+                NOTIFICATION_PREVIEW_INVALIDATED,
+                json!({ "revision": self.revision, "document": document }),
+            );
+        }
+    }
+}
+
+fn read_message(reader: &mut impl BufRead) -> io::Result<Option<Value>> {
+    let mut length = None;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.eq_ignore_ascii_case("Content-Length") {
+                length = value.trim().parse::<usize>().ok();
+            }
+        }
+    }
+
+    let length = length
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "missing content length"))?;
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body)?;
+    serde_json::from_slice(&body)
+        .map(Some)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+fn send(message: &Value) {
+    let body = message.to_string();
+    let mut stdout = io::stdout().lock();
+    let result = write!(stdout, "Content-Length: {}\r\n\r\n{}", body.len(), body)
+        .and_then(|_| stdout.flush());
+    if let Err(e) = result {
+        error!("unable to send message: {:?}", e);
+    }
+}
+
+fn notify(method: &str, params: Value) {
+    send(&json!({ "jsonrpc": "2.0", "method": method, "params": params }));
+}
+
+/// Asks the client to notify the server about changes to files of the project
+/// which are not open, such as images, bibliographies or the project config.
+fn register_file_watcher() {
+    send(&json!({
+        "jsonrpc": "2.0",
+        "id": REQUEST_WATCH_FILES,
+        "method": "client/registerCapability",
+        "params": {
+            "registrations": [{
+                "id": REQUEST_WATCH_FILES,
+                "method": "workspace/didChangeWatchedFiles",
+                "registerOptions": {
+                    "watchers": [{ "globPattern": "**/*" }],
+                },
+            }],
+        },
+    }));
+}
+
+impl Server {
+    fn handle_request(&mut self, method: &str, params: &Value) -> Result<Value, ResponseError> {
+        if method == "initialize" {
+            return Ok(self.initialize(params));
+        }
+        if self.project.is_none() {
+            return Err((
+                ERROR_SERVER_NOT_INITIALIZED,
+                "the server is not initialized".into(),
+            ));
+        }
+
+        match method {
+            "shutdown" => {
+                self.shutdown = true;
+                Ok(Value::Null)
+            }
+            "textDocument/completion" => self.completion(params),
+            "textDocument/hover" => self.hover(params),
+            _ => Err((ERROR_METHOD_NOT_FOUND, format!("unknown method {}", method))),
+        }
+    }
+
+    /// Handles a notification. Returns whether the project should be recompiled.
+    fn handle_notification(&self, method: &str, params: &Value) -> bool {
+        let Some(project) = &self.project else {
+            return false;
+        };
+
+        match method {
+            "initialized" => {
+                if self.watch_files {
+                    register_file_watcher();
+                }
+                true
+            }
+            "textDocument/didOpen" => {
+                let document = &params["textDocument"];
+                let text = document["text"].as_str().unwrap_or_default();
+                self.update(document, Some(text.to_string()))
+            }
+            "textDocument/didChange" => {
+                let Some(path) = self.source_path(&params["textDocument"]["uri"]) else {
+                    return false;
+                };
+                let mut world = project.world.lock().unwrap();
+                for change in params["contentChanges"].as_array().into_iter().flatten() {
+                    if let Err(e) = apply_change(&mut world, &path, change) {
+                        warn!("unable to apply change to {:?}: {:?}", path, e);
+                    }
+                }
+                true
+            }
+            // Reverts to the content on disk
+            "textDocument/didClose" => self.update(&params["textDocument"], None),
+            "workspace/didChangeWatchedFiles" => {
+                let changes = params["changes"].as_array().into_iter().flatten();
+                changes.fold(false, |dirty, change| self.update(change, None) || dirty)
+            }
+            _ => false,
+        }
+    }
+
+    fn initialize(&mut self, params: &Value) -> Value {
+        let root = [&params["rootUri"], &params["workspaceFolders"][0]["uri"]]
+            .into_iter()
+            .find_map(|uri| Url::parse(uri.as_str()?).ok()?.to_file_path().ok())
+            .or_else(|| params["rootPath"].as_str().map(PathBuf::from))
+            .or_else(|| std::env::current_dir().ok())
+            .unwrap_or_default();
+
+        let watch = &params["capabilities"]["workspace"]["didChangeWatchedFiles"];
+        self.watch_files = watch["dynamicRegistration"].as_bool() == Some(true);
+
+        let project = Arc::new(Project::load_from_path(root));
+        debug!("serving {:?} over lsp", project);
+        self.compiler = Some(Compiler::spawn(project.clone()));
+        self.project = Some(project);
+
+        json!({
+            "capabilities": {
+                "positionEncoding": "utf-16",
+                "textDocumentSync": {
+                    "openClose": true,
+                    // Incremental
+                    "change": 2,
+                },
+                "completionProvider": {
+                    "triggerCharacters": [" ", "(", "[", "{", "$", "@", "#", "."],
+                },
+                "hoverProvider": true,
+            },
+            "serverInfo": {
+                "name": "typstudio",
+                "version": env!("CARGO_PKG_VERSION"),
+            },
+        })
+    }
+
+    /// Resolves a document URI to a path relative to the project root.
+    fn source_path(&self, uri: &Value) -> Option<PathBuf> {
+        let project = self.project.as_ref()?;
+        let path = Url::parse(uri.as_str()?).ok()?.to_file_path().ok()?;
+        let relative = match path.strip_prefix(&project.root) {
+            Ok(relative) => relative.to_path_buf(),
+            Err(_) => path
+                .canonicalize()
+                .ok()?
+                .strip_prefix(&project.root)
+                .ok()?
+                .to_path_buf(),
+        };
+        Some(Path::new("/").join(relative))
+    }
+
+    /// Replaces the content of the file identified by `document["uri"]`, or
+    /// reloads it from disk. Returns whether the file belongs to the project.
+    fn update(&self, document: &Value, content: Option<String>) -> bool {
+        let (Some(project), Some(path)) = (&self.project, self.source_path(&document["uri"]))
+        else {
+            return false;
+        };
+
+        let relative = path.strip_prefix("/").unwrap_or(&path);
+        if is_project_config_file(relative) {
+            if let Ok(config) = ProjectConfig::read_from_file(project.root.join(relative)) {
+                debug!("updating project config for {:?}: {:?}", project, config);
+                let mut config_write = project.config.write().unwrap();
+                *config_write = config;
+                config_write.apply(project);
+            }
+            return true;
+        }
+
+        let mut world = project.world.lock().unwrap();
+        if let Err(e) = world.slot_update(&path, content) {
+            warn!(
+                "unable to update slot for {:?} in {:?}: {:?}",
+                path, project, e
+            );
+        }
+        true
+    }
+
+    /// Queues a compilation of the project.
+    fn compile(&self) {
+        if let Some(compiler) = &self.compiler {
+            let _ = compiler.send(());
+        }
+    }
+
+    fn completion(&self, params: &Value) -> Result<Value, ResponseError> {
+        let project = self.project.as_ref().unwrap();
+        let world = project.world.lock().unwrap();
+        let (source, cursor) = self.locate(&world, params)?;
+        let cache = project.cache.read().unwrap();
+
+        // Invoked, rather than triggered by a character
+        let explicit = params["context"]["triggerKind"].as_u64() == Some(1);
+        let result = ide::autocomplete(&world, cache.document.as_ref(), &source, cursor, explicit);
+        let Some((from, completions)) = result else {
+            return Ok(Value::Null);
+        };
+
+        let range = json!({
+            "start": position(&source, from),
+            "end": position(&source, cursor),
+        });
+        let items: Vec<Value> = completions
+            .into_iter()
+            .map(|completion| {
+                let is_snippet = completion.apply.is_some();
+                let text = match &completion.apply {
+                    Some(apply) => number_placeholders(apply),
+                    None => completion.label.to_string(),
+                };
+                json!({
+                    "label": completion.label,
+                    "kind": completion_kind(&completion.kind),
+                    "detail": completion.detail,
+                    // Plain text or snippet
+                    "insertTextFormat": if is_snippet { 2 } else { 1 },
+                    "textEdit": { "range": range, "newText": text },
+                })
+            })
+            .collect();
+        Ok(json!({ "isIncomplete": false, "items": items }))
+    }
+
+    fn hover(&self, params: &Value) -> Result<Value, ResponseError> {
+        let project = self.project.as_ref().unwrap();
+        let world = project.world.lock().unwrap();
+        let (source, cursor) = self.locate(&world, params)?;
+        let cache = project.cache.read().unwrap();
+
+        let value = match typst_ide::tooltip(&*world, cache.document.as_ref(), &source, cursor) {
+            Some(Tooltip::Text(text)) => text.to_string(),
+            Some(Tooltip::Code(code)) => format!("```typst\n{}\n```", code),
+            None => return Ok(Value::Null),
+        };
+        Ok(json!({ "contents": { "kind": "markdown", "value": value } }))
+    }
+
+    /// Resolves the source and the byte offset of a text document position.
+    fn locate(
+        &self,
+        world: &ProjectWorld,
+        params: &Value,
+    ) -> Result<(Source, usize), ResponseError> {
+        let invalid = || {
+            (
+                ERROR_INVALID_PARAMS,
+                "invalid text document position".into(),
+            )
+        };
+        let path = self
+            .source_path(&params["textDocument"]["uri"])
+            .ok_or_else(invalid)?;
+        let source = world
+            .source(FileId::new(None, VirtualPath::new(&path)))
+            .map_err(|e| (ERROR_INVALID_PARAMS, e.to_string()))?;
+        let cursor = offset(&source, &params["position"]).ok_or_else(invalid)?;
+        Ok((source, cursor))
+    }
+}
+
+/// Applies a content change of `textDocument/didChange`. Changes without a
+/// range replace the whole content.
+fn apply_change(
+    world: &mut ProjectWorld,
+    path: &Path,
+    change: &Value,
+) -> typst::diag::FileResult<()> {
+    let text = change["text"].as_str().unwrap_or_default().to_string();
+    let range = &change["range"];
+    if range.is_null() {
+        return world.slot_update(path, Some(text)).map(|_| ());
+    }
+
+    let source = world.source(FileId::new(None, VirtualPath::new(path)))?;
+    let utf16 = |position: &Value| {
+        offset(&source, position)
+            .and_then(|byte| source.byte_to_utf16(byte))
+            .unwrap_or(source.len_utf16())
+    };
+    let edit = TypstSourceEdit {
+        range: utf16(&range["start"])..utf16(&range["end"]),
+        text,
+    };
+    world.slot_edit(path, &[edit]).map(|_| ())
+}
+
+/// Converts an LSP position, with a column in UTF-16 code units, to a byte offset.
+fn offset(source: &Source, position: &Value) -> Option<usize> {
+    let line = position["line"].as_u64()? as usize;
+    let character = position["character"].as_u64()? as usize;
+    let Some(range) = source.line_to_range(line) else {
+        return Some(source.len_bytes());
+    };
+    let utf16 = source.byte_to_utf16(range.start)? + character;
+    Some(
+        source
+            .utf16_to_byte(utf16)
+            .unwrap_or(range.end)
+            .min(range.end),
+    )
+}
+
+/// Converts a byte offset to an LSP position.
+fn position(source: &Source, byte: usize) -> Value {
+    let line = source.byte_to_line(byte).unwrap_or_default();
+    let line_start = source.line_to_byte(line).unwrap_or_default();
+    let character = source
+        .byte_to_utf16(byte)
+        .unwrap_or_default()
+        .saturating_sub(source.byte_to_utf16(line_start).unwrap_or_default());
+    json!({ "line": line, "character": character })
+}
+
+fn map_diagnostic(world: &ProjectWorld, diagnostic: &TypstSourceDiagnostic) -> Option<Value> {
+    let source = world
+        .source(FileId::new(None, VirtualPath::new(&diagnostic.path)))
+        .ok()?;
+    let byte = |offset: usize| source.utf16_to_byte(offset).unwrap_or(source.len_bytes());
+
+    let mut message = diagnostic.message.clone();
+    for hint in &diagnostic.hints {
+        message.push_str(&format!("\nhint: {}", hint));
+    }
+    let severity = match diagnostic.severity {
+        TypstDiagnosticSeverity::Error => 1,
+        TypstDiagnosticSeverity::Warning => 2,
+    };
+
+    Some(json!({
+        "range": {
+            "start": position(&source, byte(diagnostic.range.start)),
+            "end": position(&source, byte(diagnostic.range.end)),
+        },
+        "severity": severity,
+        "source": "typst",
+        "message": message,
+    }))
+}
+
+fn file_uri(root: &Path, path: &Path) -> Option<String> {
+    let path = root.join(path.strip_prefix("/").unwrap_or(path));
+    Url::from_file_path(path).ok().map(String::from)
+}
+
+fn completion_kind(kind: &CompletionKind) -> u8 {
+    match kind {
+        CompletionKind::Syntax => 15,
+        CompletionKind::Func => 3,
+        CompletionKind::Param => 6,
+        CompletionKind::Constant => 21,
+        CompletionKind::Symbol(_) => 1,
+        CompletionKind::Type => 7,
+    }
+}
+
+/// Numbers the placeholders of a typst-ide snippet, such as `text(${})` or
+/// `${title}`, which clients would reject as invalid snippet syntax otherwise.
+fn number_placeholders(apply: &str) -> String {
+    let mut count = 0;
+    let mut parts = apply.split("${");
+    let mut snippet = parts.next().unwrap_or_default().to_string();
+    for part in parts {
+        count += 1;
+        snippet.push_str(&format!("${{{}:{}", count, part));
+    }
+    snippet
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn number_placeholders_in_order() {
+        assert_eq!(number_placeholders("text(${})"), "text(${1:})");
+        assert_eq!(
+            number_placeholders("#figure(${title}, caption: ${})"),
+            "#figure(${1:title}, caption: ${2:})"
+        );
+        assert_eq!(number_placeholders("plain"), "plain");
+    }
+}
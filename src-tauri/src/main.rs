@@ -3,8 +3,12 @@
     windows_subsystem = "windows"
 )]
 
+mod cli;
 mod engine;
+mod export;
+mod ide;
 mod ipc;
+mod lsp;
 mod menu;
 mod project;
 
@@ -17,7 +21,14 @@ use tauri::{AboutMetadata, CustomMenuItem, Menu, MenuItem, Submenu, Wry};
 
 #[tokio::main]
 async fn main() {
+    // Subcommands initialize logging themselves
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(code) = cli::run(&args) {
+        std::process::exit(code);
+    }
+
     env_logger::init_from_env(Env::default().default_filter_or("info"));
+
     info!("initializing typstudio");
 
     let project_manager = Arc::new(ProjectManager::<Wry>::new());
@@ -37,8 +48,29 @@ async fn main() {
             ipc::commands::fs_write_file_binary,
             ipc::commands::fs_write_file_text,
             ipc::commands::typst_compile,
+            ipc::commands::typst_edit,
             ipc::commands::typst_render,
+            ipc::commands::typst_export_png,
+            ipc::commands::typst_export_svg,
             ipc::commands::typst_autocomplete,
+            ipc::commands::typst_jump_from_click,
+            ipc::commands::typst_jump_from_cursor,
+            ipc::commands::typst_tooltip,
+            ipc::commands::typst_definition,
+            ipc::commands::typst_outline,
+            ipc::commands::typst_labels,
+            ipc::commands::typst_statistics,
+            ipc::commands::typst_symbols,
+            ipc::commands::typst_symbol_insertion,
+            ipc::commands::typst_docs,
+            ipc::commands::typst_semantic_tokens_legend,
+            ipc::commands::typst_semantic_tokens,
+            ipc::commands::typst_folding_ranges,
+            ipc::commands::typst_document_symbols,
+            ipc::commands::typst_code_actions,
+            ipc::commands::typst_bibliography_entry,
+            ipc::commands::typst_rename,
+            ipc::commands::typst_apply_edit,
             ipc::commands::clipboard_paste
         ])
         .run(tauri::generate_context!())
@@ -67,9 +99,12 @@ fn build_menu() -> Menu {
         )
         .add_submenu(Submenu::new(
             "Export",
-            Menu::new().add_item(
-                CustomMenuItem::new("file_export_pdf", "Export PDF").accelerator("CmdOrCtrl+E"),
-            ),
+            Menu::new()
+                .add_item(
+                    CustomMenuItem::new("file_export_pdf", "Export PDF").accelerator("CmdOrCtrl+E"),
+                )
+                .add_item(CustomMenuItem::new("file_export_png", "Export PNG"))
+                .add_item(CustomMenuItem::new("file_export_svg", "Export SVG")),
         ));
 
     #[cfg(not(target_os = "macos"))]
@@ -1,4 +1,4 @@
-use crate::ipc::events::view;
+use crate::ipc::events::{export, view};
 use crate::project::{Project, ProjectManager};
 use std::fs;
 use std::sync::Arc;
@@ -38,6 +38,12 @@ pub fn handle_menu_event<R: Runtime>(e: WindowMenuEvent<R>) {
                     }
                 }
             }),
+        "file_export_png" => {
+            export::request_png_export(e.window());
+        }
+        "file_export_svg" => {
+            export::request_svg_export(e.window());
+        }
         "view_toggle_preview" => {
             view::toggle_preview_visibility(e.window());
         }
@@ -0,0 +1,571 @@
+use crate::ide;
+use crate::ipc::{
+    TypstCompileEvent, TypstDiagnostic, TypstDiagnosticSeverity, TypstDocument, TypstOutlineEvent,
+    TypstPage, TypstSourceDiagnostic, TypstSourceEdit, TypstStatistics, TypstStatisticsEvent,
+};
+use crate::project::{Project, ProjectWorld};
+use comemo::Track;
+use log::{debug, warn};
+use siphasher::sip128::{Hasher128, SipHasher};
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::{Runtime, Window};
+use typst::diag::{Severity, SourceDiagnostic};
+use typst::engine::{Engine, Route};
+use typst::eval::Tracer;
+use typst::foundations::Context;
+use typst::introspection::Locator;
+use typst::layout::Page;
+use typst::model::Document;
+use typst::syntax::FileId;
+use typst::World;
+
+/// Time without further requests before a queued compilation starts.
+const COMPILE_DEBOUNCE: Duration = Duration::from_millis(50);
+
+/// Longest time between published results while requests keep arriving. Once
+/// it has passed, compilations start without waiting for a pause, and their
+/// results are published even if they are already stale.
+const COMPILE_PUBLISH_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Revisions are shared between projects, so that they keep increasing
+/// when the project of a window changes.
+static REVISION: AtomicU64 = AtomicU64::new(0);
+
+/// Schedules compilations of a project on a background thread. Edits are
+/// coalesced while a compilation is pending or running, and only the result
+/// of the newest revision is cached and emitted, unless requests keep arriving
+/// for longer than [COMPILE_PUBLISH_INTERVAL].
+#[derive(Default)]
+pub struct CompileScheduler {
+    state: Mutex<SchedulerState>,
+}
+
+#[derive(Default)]
+struct SchedulerState {
+    /// The newest requested revision.
+    revision: u64,
+    /// Source changes which have not been applied to the world yet, identified by path.
+    changes: HashMap<PathBuf, SourceChange>,
+    /// Whether a worker thread is currently processing requests.
+    running: bool,
+    /// When the newest revision was requested.
+    requested: Option<Instant>,
+    /// Since when requests are waiting for a result to be published.
+    waiting: Option<Instant>,
+}
+
+/// A change to a source, which is applied to the world before compiling.
+pub enum SourceChange {
+    /// Replaces the whole content of the source.
+    Replace(String),
+    /// Applies edits to the source in sequence.
+    Edit(Vec<TypstSourceEdit>),
+}
+
+/// The result of a compilation, before it has been checked for staleness.
+pub struct CompileOutput {
+    pub document: Option<Document>,
+    pub dependencies: HashSet<FileId>,
+    pub event: TypstCompileEvent,
+    /// The errors or warnings of the compilation, with their spans.
+    pub diagnostics: Vec<SourceDiagnostic>,
+}
+
+impl CompileOutput {
+    /// Caches the document and dependencies of the compilation in the project,
+    /// and determines the pages which have changed since the cached document.
+    pub fn store(&mut self, project: &Project) {
+        let mut cache = project.cache.write().unwrap();
+        if let Some(doc) = self.document.take() {
+            cache.document = Some(doc);
+        }
+        if let Some(document) = &mut self.event.document {
+            let hashes: Vec<String> = document
+                .pages
+                .iter()
+                .map(|page| page.hash.clone())
+                .collect();
+            document.changed_pages = hashes
+                .iter()
+                .enumerate()
+                .filter(|(i, hash)| cache.page_hashes.get(*i) != Some(*hash))
+                .map(|(i, _)| i)
+                .collect();
+            cache.page_hashes = hashes;
+        }
+        cache.dependencies = mem::take(&mut self.dependencies);
+        cache.diagnostics = mem::take(&mut self.diagnostics);
+    }
+}
+
+impl CompileScheduler {
+    /// Queues a compilation of the project's main source, optionally changing
+    /// the source at `path` beforehand. Returns the revision which will be
+    /// reported in the resulting [TypstCompileEvent].
+    pub fn request<R: Runtime>(
+        &self,
+        project: &Arc<Project>,
+        window: &Window<R>,
+        change: Option<(PathBuf, SourceChange)>,
+    ) -> u64 {
+        let mut state = self.state.lock().unwrap();
+        state.revision = REVISION.fetch_add(1, Ordering::Relaxed) + 1;
+        let now = Instant::now();
+        state.requested = Some(now);
+        state.waiting.get_or_insert(now);
+        if let Some((path, change)) = change {
+            let change = merge_change(state.changes.remove(&path), change);
+            state.changes.insert(path, change);
+        }
+
+        if !state.running {
+            state.running = true;
+
+            let project = project.clone();
+            let window = window.clone();
+            thread::spawn(move || project.compiler.run(&project, &window));
+        }
+
+        state.revision
+    }
+
+    /// Applies the pending source changes to the world, so that it can be
+    /// queried before the queued compilation starts.
+    pub fn flush(&self, project: &Project, world: &mut ProjectWorld) {
+        let changes = mem::take(&mut self.state.lock().unwrap().changes);
+        for (path, change) in changes {
+            apply_change(project, world, &path, change);
+        }
+    }
+
+    fn run<R: Runtime>(&self, project: &Project, window: &Window<R>) {
+        loop {
+            self.debounce();
+
+            // The world is locked before the changes are taken, so that a flush
+            // cannot observe the world without changes which have been taken.
+            // A snapshot is compiled, so that other commands can use the world
+            // in the meantime.
+            let (revision, mut snapshot) = {
+                let mut world = project.world.lock().unwrap();
+                let (revision, changes) = {
+                    let mut state = self.state.lock().unwrap();
+                    (state.revision, mem::take(&mut state.changes))
+                };
+                for (path, change) in changes {
+                    apply_change(project, &mut world, &path, change);
+                }
+                (revision, world.snapshot())
+            };
+            let output = compile(project, &mut snapshot, revision);
+            // The snapshot is kept to build the outline of the document
+            let loaded = snapshot.snapshot();
+            project.world.lock().unwrap().merge_snapshot(loaded);
+
+            let mut state = self.state.lock().unwrap();
+            let stale = state.revision != revision;
+            let overdue = state
+                .waiting
+                .is_some_and(|waiting| waiting.elapsed() >= COMPILE_PUBLISH_INTERVAL);
+            let compiled = output
+                .as_ref()
+                .is_some_and(|output| output.document.is_some());
+            if stale && !(overdue && compiled) {
+                debug!(
+                    "discarding stale compilation of revision {} for {:?}",
+                    revision, project
+                );
+                continue;
+            }
+            match stale {
+                true => state.waiting = Some(Instant::now()),
+                false => {
+                    state.running = false;
+                    state.waiting = None;
+                }
+            }
+
+            // The event is emitted while holding the state lock, to ensure that
+            // a newer worker cannot emit its result before this one.
+            let Some(mut output) = output else {
+                return;
+            };
+            output.store(project);
+
+            let _ = window.emit("typst_compile", output.event);
+            drop(state);
+
+            // The outline is built from the snapshot the document was compiled
+            // from, once the result is known to be published
+            if compiled {
+                let document = project.cache.read().unwrap().document.clone();
+                if let Some(document) = document {
+                    let items = ide::outline(&snapshot, &document);
+                    let _ = window.emit("typst_outline", TypstOutlineEvent { revision, items });
+                }
+            }
+
+            if stale {
+                continue;
+            }
+
+            // Statistics walk the whole document, so they are only computed for
+            // results which are not stale, and without holding the world lock.
+            // The frontend discards statistics which arrive out-of-order.
+            if compiled {
+                let statistics = project_statistics(project);
+                let _ = window.emit(
+                    "typst_statistics",
+                    TypstStatisticsEvent {
+                        revision,
+                        statistics,
+                    },
+                );
+            }
+            return;
+        }
+    }
+
+    /// Waits until no compilation has been requested for [COMPILE_DEBOUNCE],
+    /// or until requests have been waiting for [COMPILE_PUBLISH_INTERVAL].
+    fn debounce(&self) {
+        loop {
+            let wait = {
+                let state = self.state.lock().unwrap();
+                let pause = state.requested.map_or(Duration::ZERO, |requested| {
+                    COMPILE_DEBOUNCE.saturating_sub(requested.elapsed())
+                });
+                let overdue = state.waiting.map_or(pause, |waiting| {
+                    COMPILE_PUBLISH_INTERVAL.saturating_sub(waiting.elapsed())
+                });
+                pause.min(overdue)
+            };
+            if wait.is_zero() {
+                return;
+            }
+            thread::sleep(wait);
+        }
+    }
+}
+
+/// Computes the statistics of the project's cached document. Only the sources
+/// are collected while the world is locked.
+pub fn project_statistics(project: &Project) -> TypstStatistics {
+    let (sources, document) = {
+        let world = project.world.lock().unwrap();
+        let cache = project.cache.read().unwrap();
+        let sources = ide::StatisticsSources::collect(&world, &cache.dependencies);
+        (sources, cache.document.clone())
+    };
+    ide::statistics(&sources, document.as_ref())
+}
+
+fn apply_change(project: &Project, world: &mut ProjectWorld, path: &Path, change: SourceChange) {
+    let result = match change {
+        SourceChange::Replace(content) => world.slot_update(path, Some(content)),
+        SourceChange::Edit(edits) => world.slot_edit(path, &edits),
+    };
+    if let Err(e) = result {
+        warn!(
+            "unable to update slot for {:?} in {:?}: {:?}",
+            path, project, e
+        );
+    }
+}
+
+/// Combines a pending change of a source with a newer one, so that only a
+/// single change has to be applied.
+fn merge_change(pending: Option<SourceChange>, change: SourceChange) -> SourceChange {
+    match (pending, change) {
+        (Some(SourceChange::Edit(mut pending)), SourceChange::Edit(edits)) => {
+            pending.extend(edits);
+            SourceChange::Edit(pending)
+        }
+        (Some(SourceChange::Replace(mut content)), SourceChange::Edit(edits)) => {
+            for edit in edits {
+                let start = utf16_to_byte(&content, edit.range.start);
+                let end = utf16_to_byte(&content, edit.range.end).max(start);
+                content.replace_range(start..end, &edit.text);
+            }
+            SourceChange::Replace(content)
+        }
+        (_, change) => change,
+    }
+}
+
+/// Converts an offset in UTF-16 code units to a byte offset, clamped to the
+/// length of the text.
+fn utf16_to_byte(text: &str, offset: usize) -> usize {
+    let mut utf16 = 0;
+    for (i, c) in text.char_indices() {
+        if utf16 >= offset {
+            return i;
+        }
+        utf16 += c.len_utf16();
+    }
+    text.len()
+}
+
+/// Compiles the main source of the project. Returns [Option::None] if the
+/// main source is not configured.
+pub fn compile(
+    project: &Project,
+    world: &mut ProjectWorld,
+    revision: u64,
+) -> Option<CompileOutput> {
+    if !world.is_main_set() {
+        let config = project.config.read().unwrap();
+        if config.apply_main(project, world).is_err() {
+            debug!("skipped compilation for {:?} (main not set)", project);
+            return None;
+        }
+    }
+
+    debug!("compiling revision {} of {:?}", revision, project);
+    let now = Instant::now();
+    let mut tracer = Tracer::new();
+    world.reset_access();
+    let result = typst::compile(&*world, &mut tracer);
+    let dependencies = world.dependencies();
+
+    let output = match result {
+        Ok(doc) => {
+            let elapsed = now.elapsed();
+            debug!(
+                "compilation succeeded for {:?} in {:?} ms",
+                project,
+                elapsed.as_millis()
+            );
+
+            let pages = map_pages(&*world, &doc);
+
+            let mut hasher = SipHasher::new();
+            for page in &pages {
+                page.hash.hash(&mut hasher);
+            }
+            let hash = hex::encode(hasher.finish128().as_bytes());
+
+            let warnings = tracer.warnings();
+            let (mapped, detached_diagnostics) = map_diagnostics(&*world, &warnings);
+
+            CompileOutput {
+                event: TypstCompileEvent {
+                    revision,
+                    document: Some(TypstDocument {
+                        // Determined once the result is known not to be stale
+                        changed_pages: vec![],
+                        pages,
+                        hash,
+                    }),
+                    diagnostics: Some(mapped),
+                    detached_diagnostics: Some(detached_diagnostics),
+                },
+                document: Some(doc),
+                dependencies,
+                diagnostics: warnings.to_vec(),
+            }
+        }
+        Err(diagnostics) => {
+            debug!(
+                "compilation failed with {:?} diagnostics",
+                diagnostics.len()
+            );
+
+            let (mapped, detached_diagnostics) = map_diagnostics(&*world, &diagnostics);
+
+            CompileOutput {
+                event: TypstCompileEvent {
+                    revision,
+                    document: None,
+                    diagnostics: Some(mapped),
+                    detached_diagnostics: Some(detached_diagnostics),
+                },
+                document: None,
+                dependencies,
+                diagnostics: diagnostics.to_vec(),
+            }
+        }
+    };
+
+    Some(output)
+}
+
+fn map_pages(world: &dyn World, document: &Document) -> Vec<TypstPage> {
+    // Numbering functions, e.g. roman numerals for the front matter, are
+    // called with an engine over the compiled document
+    let mut locator = Locator::new();
+    let mut tracer = Tracer::new();
+    let mut engine = Engine {
+        world: world.track(),
+        route: Route::default(),
+        introspector: document.introspector.track(),
+        locator: &mut locator,
+        tracer: tracer.track_mut(),
+    };
+
+    document
+        .pages
+        .iter()
+        .map(|page| map_page(&mut engine, page))
+        .collect()
+}
+
+fn map_page(engine: &mut Engine, page: &Page) -> TypstPage {
+    let mut hasher = SipHasher::new();
+    page.frame.hash(&mut hasher);
+    let hash = hex::encode(hasher.finish128().as_bytes());
+
+    let label = page.numbering.as_ref().map(|numbering| {
+        let context = Context::new(None, None);
+        numbering
+            .apply(engine, context.track(), &[page.number])
+            .map(|value| value.display().plain_text().to_string())
+            .unwrap_or_else(|_| page.number.to_string())
+    });
+
+    TypstPage {
+        number: page.number,
+        label,
+        hash,
+        width: page.frame.width().to_pt(),
+        height: page.frame.height().to_pt(),
+    }
+}
+
+impl From<Severity> for TypstDiagnosticSeverity {
+    fn from(value: Severity) -> Self {
+        match value {
+            Severity::Error => TypstDiagnosticSeverity::Error,
+            Severity::Warning => TypstDiagnosticSeverity::Warning,
+        }
+    }
+}
+
+/// Maps diagnostics to their source files. Diagnostics with detached spans, or
+/// spans pointing to sources which can no longer be loaded, are returned separately.
+pub fn map_diagnostics<W: World>(
+    world: &W,
+    diagnostics: &[SourceDiagnostic],
+) -> (Vec<TypstSourceDiagnostic>, Vec<TypstDiagnostic>) {
+    let mut sources = vec![];
+    let mut detached = vec![];
+
+    for diagnostic in diagnostics {
+        let severity = diagnostic.severity.into();
+        let message = diagnostic.message.to_string();
+        let hints = diagnostic
+            .hints
+            .iter()
+            .map(|hint| hint.to_string())
+            .collect();
+
+        let located = diagnostic.span.id().and_then(|id| {
+            let source = world.source(id).ok()?;
+            let range = source.range(diagnostic.span)?;
+            Some((id, source, range))
+        });
+
+        match located {
+            Some((id, source, range)) => {
+                sources.push(TypstSourceDiagnostic {
+                    path: id.vpath().as_rooted_path().to_path_buf(),
+                    package: id.package().map(|spec| spec.to_string()),
+                    range: ide::utf16_range(&source, range),
+                    severity,
+                    message,
+                    hints,
+                });
+            }
+            None => detached.push(TypstDiagnostic {
+                severity,
+                message,
+                hints,
+            }),
+        }
+    }
+
+    (sources, detached)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edit(start: usize, end: usize, text: &str) -> TypstSourceEdit {
+        TypstSourceEdit {
+            range: start..end,
+            text: text.to_string(),
+        }
+    }
+
+    fn replaced(change: SourceChange) -> String {
+        match change {
+            SourceChange::Replace(content) => content,
+            SourceChange::Edit(_) => panic!("expected a replacement"),
+        }
+    }
+
+    #[test]
+    fn test_utf16_to_byte() {
+        // `ä` takes one code unit and two bytes, `😀` two code units and four bytes
+        let text = "aä😀b";
+        assert_eq!(utf16_to_byte(text, 0), 0);
+        assert_eq!(utf16_to_byte(text, 1), 1);
+        assert_eq!(utf16_to_byte(text, 2), 3);
+        assert_eq!(utf16_to_byte(text, 4), 7);
+        assert_eq!(utf16_to_byte(text, 5), 8);
+    }
+
+    #[test]
+    fn test_utf16_to_byte_within_surrogate_pair() {
+        // Offsets within a character round up to the next character boundary
+        assert_eq!(utf16_to_byte("a😀b", 2), 5);
+    }
+
+    #[test]
+    fn test_utf16_to_byte_out_of_range() {
+        assert_eq!(utf16_to_byte("aä😀b", 100), 8);
+        assert_eq!(utf16_to_byte("", 1), 0);
+    }
+
+    #[test]
+    fn test_merge_edits() {
+        let pending = SourceChange::Edit(vec![edit(0, 0, "a")]);
+        let change = SourceChange::Edit(vec![edit(1, 1, "b"), edit(2, 2, "c")]);
+        match merge_change(Some(pending), change) {
+            SourceChange::Edit(edits) => {
+                let texts: Vec<&str> = edits.iter().map(|edit| edit.text.as_str()).collect();
+                assert_eq!(texts, ["a", "b", "c"]);
+            }
+            SourceChange::Replace(_) => panic!("expected edits"),
+        }
+    }
+
+    #[test]
+    fn test_merge_replace_and_edits() {
+        let pending = SourceChange::Replace("aä😀b".to_string());
+        let change = SourceChange::Edit(vec![edit(2, 4, "🎉"), edit(0, 1, ""), edit(3, 3, "!")]);
+        assert_eq!(replaced(merge_change(Some(pending), change)), "ä🎉!b");
+    }
+
+    #[test]
+    fn test_merge_edits_and_replace() {
+        let pending = SourceChange::Edit(vec![edit(0, 0, "a")]);
+        let change = SourceChange::Replace("b".to_string());
+        assert_eq!(replaced(merge_change(Some(pending), change)), "b");
+    }
+
+    #[test]
+    fn test_merge_out_of_range_edits() {
+        let pending = SourceChange::Replace("ab".to_string());
+        let change = SourceChange::Edit(vec![edit(5, 10, "c"), edit(3, 1, "d")]);
+        assert_eq!(replaced(merge_change(Some(pending), change)), "abcd");
+    }
+}
@@ -1,9 +1,10 @@
-use crate::ipc::{FSRefreshEvent, ProjectChangeEvent, ProjectModel};
+use crate::ipc::{FSRefreshEvent, FSReloadEvent, ProjectChangeEvent, ProjectModel};
 use crate::project::{is_project_config_file, Project, ProjectConfig};
 use log::{debug, error, info, trace, warn};
-use notify::event::ModifyKind;
+use notify::event::{ModifyKind, RenameMode};
 use notify::{Config, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
 use std::collections::HashMap;
+use std::fs;
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex, RwLock};
 use tauri::{Runtime, Window};
@@ -13,6 +14,8 @@ use tokio::sync::mpsc::channel;
 enum FSHandleKind {
     Refresh,
     Reload,
+    /// A file was created or renamed, which is how some editors save files.
+    Replace,
 }
 
 pub struct ProjectManager<R: Runtime> {
@@ -93,24 +96,36 @@ impl<R: Runtime> ProjectManager<R> {
     }
 
     fn handle_fs_event(&self, event: notify::Event) {
-        let opt = match event.kind {
-            EventKind::Create(_) | EventKind::Remove(_) => event.paths[0]
-                .parent()
-                .map(|p| (p.to_path_buf(), FSHandleKind::Refresh)),
+        let refresh = |path: &PathBuf| {
+            path.parent()
+                .map(|p| (p.to_path_buf(), FSHandleKind::Refresh))
+        };
+        let handles: Vec<(PathBuf, FSHandleKind)> = match event.kind {
+            EventKind::Create(_) => vec![(event.paths[0].clone(), FSHandleKind::Replace)],
+            EventKind::Remove(_) => refresh(&event.paths[0]).into_iter().collect(),
             EventKind::Modify(kind) => match kind {
-                ModifyKind::Name(_) => event.paths[0]
-                    .parent()
-                    .map(|p| (p.to_path_buf(), FSHandleKind::Refresh)),
-                ModifyKind::Data(_) => Some((event.paths[0].clone(), FSHandleKind::Reload)),
-                _ => None,
+                ModifyKind::Name(RenameMode::Both) => refresh(&event.paths[0])
+                    .into_iter()
+                    .chain(
+                        event
+                            .paths
+                            .get(1)
+                            .map(|to| (to.clone(), FSHandleKind::Replace)),
+                    )
+                    .collect(),
+                ModifyKind::Name(RenameMode::To) => {
+                    vec![(event.paths[0].clone(), FSHandleKind::Replace)]
+                }
+                ModifyKind::Name(_) => refresh(&event.paths[0]).into_iter().collect(),
+                ModifyKind::Data(_) => vec![(event.paths[0].clone(), FSHandleKind::Reload)],
+                _ => vec![],
             },
-            _ => None,
+            _ => vec![],
         };
 
-        if let Some((path, kind)) = opt {
+        let projects = self.projects.read().unwrap();
+        for (path, kind) in handles {
             let path = path.canonicalize().unwrap_or(path);
-            let projects = self.projects.read().unwrap();
-
             for (window, project) in &*projects {
                 if path.starts_with(&project.root) {
                     self.handle_project_fs_event(project, window, &path, kind);
@@ -121,7 +136,7 @@ impl<R: Runtime> ProjectManager<R> {
 
     fn handle_project_fs_event(
         &self,
-        project: &Project,
+        project: &Arc<Project>,
         window: &Window<R>,
         path: &PathBuf,
         kind: FSHandleKind,
@@ -142,6 +157,25 @@ impl<R: Runtime> ProjectManager<R> {
                     let _ = window.emit("fs_refresh", &event);
                 }
             }
+            // Refreshes the explorer view, and reloads the file if it is in use, so
+            // that dependents are recompiled
+            FSHandleKind::Replace => {
+                if let Some(parent) = path.parent() {
+                    let parent = parent.to_path_buf();
+                    self.handle_project_fs_event(project, window, &parent, FSHandleKind::Refresh);
+                }
+                let is_used = path.strip_prefix(&project.root).is_ok_and(|relative| {
+                    is_project_config_file(relative)
+                        || project
+                            .world
+                            .lock()
+                            .unwrap()
+                            .has_slot(Path::new("/").join(relative))
+                });
+                if is_used {
+                    self.handle_project_fs_event(project, window, path, FSHandleKind::Reload);
+                }
+            }
             // Reloads the file content, eg. project config or project source files
             FSHandleKind::Reload => {
                 if let Ok(relative) = path.strip_prefix(&project.root) {
@@ -153,11 +187,35 @@ impl<R: Runtime> ProjectManager<R> {
                             config_write.apply(project);
                         }
                     } else {
+                        let content = fs::read(path);
                         let mut world = project.world.lock().unwrap();
                         let path = Path::new("/").join(relative);
+
+                        // Writes of the editor are skipped, as the source may
+                        // already contain newer edits than the file
+                        if content.is_ok_and(|content| world.is_slot_written(&path, &content)) {
+                            trace!("skipping reload of {:?} written by the editor", path);
+                            return;
+                        }
+
+                        project.compiler.flush(project, &mut world);
                         match world.slot_update(&path, None) {
                             Ok(id) => {
                                 debug!("updated slot for {:?} {:?} in {:?}", path, id, project);
+                                drop(world);
+
+                                // Edits of the editor are based on the previous content,
+                                // so it has to send its whole content again
+                                let event = FSReloadEvent { path: path.clone() };
+                                let _ = window.emit("fs_reload", &event);
+
+                                // Recompile if the last compilation depended on the file
+                                let is_dependency =
+                                    project.cache.read().unwrap().dependencies.contains(&id);
+                                if is_dependency {
+                                    debug!("recompiling {:?} as {:?} has changed", project, path);
+                                    project.compiler.request(project, window, None);
+                                }
                             }
                             Err(e) => {
                                 warn!(
@@ -1,7 +1,9 @@
+mod compiler;
 mod project;
 mod world;
 mod manager;
 
+pub use compiler::*;
 pub use project::*;
 pub use world::*;
 pub use manager::*;
@@ -1,14 +1,15 @@
-use crate::project::ProjectWorld;
+use crate::project::{CompileScheduler, ProjectWorld};
 use log::debug;
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::fmt::{Debug, Formatter};
 use std::path::{Path, PathBuf};
 use std::sync::{Mutex, RwLock};
 use std::{fs, io};
 use thiserror::Error;
-use typst::diag::{FileError, FileResult};
+use typst::diag::{FileError, FileResult, SourceDiagnostic};
 use typst::model::Document;
-use typst::syntax::VirtualPath;
+use typst::syntax::{FileId, VirtualPath};
 
 const PATH_PROJECT_CONFIG_FILE: &str = ".typstudio/project.json";
 
@@ -17,11 +18,20 @@ pub struct Project {
     pub world: Mutex<ProjectWorld>,
     pub cache: RwLock<ProjectCache>,
     pub config: RwLock<ProjectConfig>,
+    pub compiler: CompileScheduler,
 }
 
 #[derive(Default)]
 pub struct ProjectCache {
     pub document: Option<Document>,
+    /// Frame hashes of the cached document's pages.
+    pub page_hashes: Vec<String>,
+    /// Files which were read by the last compilation.
+    pub dependencies: HashSet<FileId>,
+    /// The semantic tokens last sent for each source, with their result id.
+    pub semantic_tokens: HashMap<FileId, (String, Vec<u32>)>,
+    /// Errors or warnings of the last compilation, from which fixes are derived.
+    pub diagnostics: Vec<SourceDiagnostic>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, Hash)]
@@ -95,6 +105,7 @@ impl Project {
             world: ProjectWorld::new(path.clone()).into(),
             cache: RwLock::new(Default::default()),
             config: RwLock::new(config),
+            compiler: CompileScheduler::default(),
             root: path,
         }
     }
@@ -1,10 +1,13 @@
 use crate::engine::TypstEngine;
+use crate::ipc::TypstSourceEdit;
 use chrono::Datelike;
 use comemo::Prehashed;
+use siphasher::sip128::{Hasher128, SipHasher};
 use std::cell::{OnceCell, RefCell, RefMut};
 use std::collections::hash_map::Entry;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs;
+use std::hash::Hasher;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use typst::diag::{FileError, FileResult, PackageError, PackageResult};
@@ -13,6 +16,7 @@ use typst::syntax::package::PackageSpec;
 use typst::syntax::{FileId, Source, VirtualPath};
 use typst::text::{Font, FontBook};
 use typst::{Library, World};
+use walkdir::WalkDir;
 
 pub struct ProjectWorld {
     root: PathBuf,
@@ -36,6 +40,7 @@ impl ProjectWorld {
         let vpath = VirtualPath::new(path);
         let id = FileId::new(None, vpath.clone());
         let mut slot = self.slot(id)?;
+        slot.version += 1;
 
         if let Some(res) = slot.buffer.get_mut() {
             // TODO: Avoid cloning?
@@ -49,21 +54,157 @@ impl ProjectWorld {
                 }
             }
         };
-        if let Some(res) = slot.source.get_mut() {
-            let content = self.take_or_read(&vpath, content)?;
-            match res {
-                Ok(src) => {
-                    // TODO: incremental edits
-                    src.replace(&content);
+        match slot.source.get_mut() {
+            Some(res) => {
+                let content = self.take_or_read(&vpath, content)?;
+                match res {
+                    Ok(src) => {
+                        // Only the changed range is reparsed. Use [ProjectWorld::slot_edit]
+                        // to avoid diffing the whole content.
+                        src.replace(&content);
+                    }
+                    Err(_) => {
+                        *res = Ok(Source::new(id, content));
+                    }
                 }
-                Err(_) => {
-                    *res = Ok(Source::new(id, content));
+            }
+            // Keeps the content, which may differ from the file, until the source is accessed
+            None => {
+                if let Some(content) = content {
+                    let _ = slot.source.set(Ok(Source::new(id, content)));
                 }
             }
+        }
+        Ok(id)
+    }
+
+    /// Remembers the content written to the file at `path` by the editor, so
+    /// that the resulting file system event can be told apart from external
+    /// changes.
+    pub fn slot_written<P: AsRef<Path>>(&mut self, path: P, content: &[u8]) -> FileResult<()> {
+        let id = FileId::new(None, VirtualPath::new(path));
+        self.slot(id)?.written = Some(content_hash(content));
+        Ok(())
+    }
+
+    /// Whether `content` is what the editor last wrote to the file at `path`.
+    pub fn is_slot_written<P: AsRef<Path>>(&self, path: P, content: &[u8]) -> bool {
+        let id = FileId::new(None, VirtualPath::new(path));
+        self.slots
+            .borrow()
+            .get(&id)
+            .is_some_and(|slot| slot.written == Some(content_hash(content)))
+    }
+
+    /// Whether the file at `path` has a slot, e.g. because it has been read.
+    pub fn has_slot<P: AsRef<Path>>(&self, path: P) -> bool {
+        let id = FileId::new(None, VirtualPath::new(path));
+        self.slots.borrow().contains_key(&id)
+    }
+
+    /// Applies edits to the source at `path` in sequence, loading it from the
+    /// file system first if it is not in memory yet.
+    pub fn slot_edit<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+        edits: &[TypstSourceEdit],
+    ) -> FileResult<FileId> {
+        let id = FileId::new(None, VirtualPath::new(path));
+        let mut slot = self.slot(id)?;
+        slot.source()?;
+        slot.version += 1;
+
+        let slot = &mut *slot;
+        let src = match slot.source.get_mut() {
+            Some(Ok(src)) => src,
+            _ => return Err(FileError::Other(None)),
         };
+        for edit in edits {
+            let len = src.len_bytes();
+            let start = src.utf16_to_byte(edit.range.start).unwrap_or(len);
+            let end = src.utf16_to_byte(edit.range.end).unwrap_or(len).max(start);
+            src.edit(start..end, &edit.text);
+        }
+        if let Some(res) = slot.buffer.get_mut() {
+            *res = Ok(Bytes::from(src.text().as_bytes().to_vec()));
+        }
         Ok(id)
     }
 
+    /// Copies the world, so that it can be compiled without holding the lock of
+    /// the project's world. Sources and files are shared rather than copied.
+    pub fn snapshot(&self) -> Self {
+        Self {
+            root: self.root.clone(),
+            engine: self.engine.clone(),
+            slots: RefCell::new(self.slots.borrow().clone()),
+            main: self.main,
+        }
+    }
+
+    /// Keeps the files which were loaded while compiling a snapshot of this
+    /// world, unless they have been changed since the snapshot was taken.
+    pub fn merge_snapshot(&mut self, snapshot: ProjectWorld) {
+        let slots = self.slots.get_mut();
+        for (id, loaded) in snapshot.slots.into_inner() {
+            let slot = match slots.entry(id) {
+                Entry::Vacant(entry) => {
+                    entry.insert(loaded);
+                    continue;
+                }
+                Entry::Occupied(entry) => entry.into_mut(),
+            };
+            if slot.version != loaded.version {
+                continue;
+            }
+            if let (None, Some(source)) = (slot.source.get(), loaded.source.into_inner()) {
+                let _ = slot.source.set(source);
+            }
+            if let (None, Some(buffer)) = (slot.buffer.get(), loaded.buffer.into_inner()) {
+                let _ = slot.buffer.set(buffer);
+            }
+        }
+    }
+
+    /// Clears the access markers of all slots. This should be called before
+    /// compilation, so that [ProjectWorld::dependencies] only returns the files
+    /// which were read by that compilation.
+    pub fn reset_access(&mut self) {
+        for slot in self.slots.get_mut().values_mut() {
+            slot.accessed = false;
+        }
+    }
+
+    /// Returns the files which were accessed since [ProjectWorld::reset_access]
+    /// was last called.
+    pub fn dependencies(&self) -> HashSet<FileId> {
+        self.slots
+            .borrow()
+            .values()
+            .filter(|slot| slot.accessed)
+            .map(|slot| slot.id)
+            .collect()
+    }
+
+    /// Returns the ids of all Typst sources within the project root. Hidden
+    /// files and directories, such as `.typstudio`, are skipped.
+    pub fn source_ids(&self) -> Vec<FileId> {
+        WalkDir::new(&self.root)
+            .sort_by(|a, b| a.file_name().cmp(b.file_name()))
+            .into_iter()
+            .filter_entry(|entry| {
+                entry.depth() == 0 || !entry.file_name().to_string_lossy().starts_with('.')
+            })
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| {
+                entry.file_type().is_file()
+                    && entry.path().extension().and_then(|s| s.to_str()) == Some("typ")
+            })
+            .filter_map(|entry| VirtualPath::within_root(entry.path(), &self.root))
+            .map(|vpath| FileId::new(None, vpath))
+            .collect()
+    }
+
     pub fn set_main(&mut self, id: Option<FileId>) {
         self.main = id
     }
@@ -105,8 +246,11 @@ impl ProjectWorld {
             slots.entry(id).or_insert_with(|| PathSlot {
                 id,
                 path,
+                accessed: false,
                 source: OnceCell::new(),
                 buffer: OnceCell::new(),
+                written: None,
+                version: 0,
             })
         }))
     }
@@ -175,11 +319,15 @@ impl World for ProjectWorld {
     }
 
     fn source(&self, id: FileId) -> FileResult<Source> {
-        self.slot(id)?.source()
+        let mut slot = self.slot(id)?;
+        slot.accessed = true;
+        slot.source()
     }
 
     fn file(&self, id: FileId) -> FileResult<Bytes> {
-        self.slot(id)?.file()
+        let mut slot = self.slot(id)?;
+        slot.accessed = true;
+        slot.file()
     }
 
     fn font(&self, id: usize) -> Option<Font> {
@@ -206,11 +354,25 @@ impl World for ProjectWorld {
     }
 }
 
+fn content_hash(content: &[u8]) -> u128 {
+    let mut hasher = SipHasher::new();
+    hasher.write(content);
+    hasher.finish128().as_u128()
+}
+
+#[derive(Clone)]
 struct PathSlot {
     id: FileId,
     path: PathBuf,
+    /// Whether the file was accessed through [World] since the last reset.
+    accessed: bool,
     source: OnceCell<FileResult<Source>>,
     buffer: OnceCell<FileResult<Bytes>>,
+    /// Hash of the content last written to the file by the editor.
+    written: Option<u128>,
+    /// Incremented whenever the content is changed, so that snapshots do not
+    /// overwrite newer content when they are merged.
+    version: u64,
 }
 
 impl PathSlot {
@@ -235,3 +397,87 @@ impl PathSlot {
             .clone()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const PATH: &str = "/main.typ";
+
+    /// Creates a world in a temporary directory, which is removed once the
+    /// returned [TempDir] is dropped.
+    fn world(content: &str) -> (TempDir, ProjectWorld) {
+        let dir = tempfile::tempdir().unwrap();
+        let mut world = ProjectWorld::new(dir.path().to_path_buf());
+        world.slot_update(PATH, Some(content.to_string())).unwrap();
+        (dir, world)
+    }
+
+    fn edit(start: usize, end: usize, text: &str) -> TypstSourceEdit {
+        TypstSourceEdit {
+            range: start..end,
+            text: text.to_string(),
+        }
+    }
+
+    fn text(world: &ProjectWorld) -> String {
+        let id = FileId::new(None, VirtualPath::new(PATH));
+        world.source(id).unwrap().text().to_string()
+    }
+
+    #[test]
+    fn test_slot_edit_multi_byte() {
+        // `ä` takes one code unit and two bytes, `😀` two code units and four bytes
+        let (_dir, mut world) = world("aä😀b");
+        world.slot_edit(PATH, &[edit(2, 4, "🎉")]).unwrap();
+        assert_eq!(text(&world), "aä🎉b");
+        world.slot_edit(PATH, &[edit(1, 2, "o")]).unwrap();
+        assert_eq!(text(&world), "ao🎉b");
+    }
+
+    #[test]
+    fn test_slot_edit_sequence() {
+        let (_dir, mut world) = world("hello");
+        let edits = [edit(5, 5, " world"), edit(0, 1, "H"), edit(11, 11, "!")];
+        world.slot_edit(PATH, &edits).unwrap();
+        assert_eq!(text(&world), "Hello world!");
+    }
+
+    #[test]
+    fn test_slot_edit_multiple_lines() {
+        let (_dir, mut world) = world("= A\n😀 b\n");
+        world.slot_edit(PATH, &[edit(7, 8, "c")]).unwrap();
+        assert_eq!(text(&world), "= A\n😀 c\n");
+    }
+
+    #[test]
+    fn test_slot_edit_out_of_range() {
+        let (_dir, mut world) = world("ab");
+        world
+            .slot_edit(PATH, &[edit(10, 20, "c"), edit(3, 1, "d")])
+            .unwrap();
+        assert_eq!(text(&world), "abcd");
+    }
+
+    #[test]
+    fn test_slot_written() {
+        let (_dir, mut world) = world("a");
+        world.slot_written(PATH, b"a").unwrap();
+        assert!(world.is_slot_written(PATH, b"a"));
+        assert!(!world.is_slot_written(PATH, b"b"));
+    }
+
+    #[test]
+    fn test_merge_snapshot() {
+        let (_dir, mut world) = world("a");
+        let mut snapshot = world.snapshot();
+        snapshot
+            .slot_update("/other.typ", Some("b".into()))
+            .unwrap();
+        world.merge_snapshot(snapshot);
+
+        let id = FileId::new(None, VirtualPath::new("/other.typ"));
+        assert_eq!(world.source(id).unwrap().text(), "b");
+    }
+}
@@ -0,0 +1,61 @@
+import type { CancellationToken, editor, IRange, Range } from "monaco-editor";
+import { languages } from "monaco-editor";
+
+import { codeActions, createFile } from "../ipc";
+import { syncSource } from "./sync";
+
+// Registered in monaco.ts, since commands are not tied to a provider
+export const CREATE_FILE_COMMAND = "typst.createFile";
+
+export class TypstCodeActionProvider implements languages.CodeActionProvider {
+  async provideCodeActions(
+    model: editor.ITextModel,
+    range: Range,
+    context: languages.CodeActionContext,
+    token: CancellationToken
+  ): Promise<languages.CodeActionList> {
+    await syncSource(model);
+    const actions = await codeActions(model.uri.path, null, {
+      start: model.getOffsetAt(range.getStartPosition()),
+      end: model.getOffsetAt(range.getEndPosition()),
+    });
+
+    const toRange = ({ start, end }: { start: number; end: number }): IRange => {
+      const startPosition = model.getPositionAt(start);
+      const endPosition = model.getPositionAt(end);
+      return {
+        startLineNumber: startPosition.lineNumber,
+        startColumn: startPosition.column,
+        endLineNumber: endPosition.lineNumber,
+        endColumn: endPosition.column,
+      };
+    };
+
+    const versionId = model.getVersionId();
+    return {
+      actions: actions.map((action) => ({
+        title: action.title,
+        kind: "quickfix",
+        // Markers include the hints of the diagnostic after its message
+        diagnostics: context.markers.filter((marker) => marker.message.startsWith(action.message)),
+        // Fixes only edit the source they are requested for
+        edit: {
+          edits: action.edit.files
+            .filter((file) => file.path === model.uri.path)
+            .flatMap((file) => file.edits)
+            .map(({ range, text }) => ({
+              resource: model.uri,
+              versionId,
+              textEdit: { range: toRange(range), text },
+            })),
+        },
+        command: action.create_file
+          ? { id: CREATE_FILE_COMMAND, title: action.title, arguments: [action.create_file] }
+          : undefined,
+      })),
+      dispose: () => {},
+    };
+  }
+}
+
+export const createFileCommand = (accessor: unknown, path: string) => createFile(path);
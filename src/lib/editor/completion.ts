@@ -2,6 +2,7 @@ import type { CancellationToken, editor, IRange, Position } from "monaco-editor"
 import { languages } from "monaco-editor";
 
 import { autocomplete, TypstCompletionKind } from "../ipc";
+import { syncSource } from "./sync";
 
 import CompletionTriggerKind = languages.CompletionTriggerKind;
 
@@ -15,9 +16,11 @@ export class TypstCompletionProvider implements languages.CompletionItemProvider
     token: CancellationToken
   ): Promise<languages.CompletionList> {
     console.log("completing", position, context);
+    // The backend keeps the source in sync, so only pending edits are sent
+    await syncSource(model);
     const { offset: completionOffset, completions } = await autocomplete(
       model.uri.path,
-      model.getValue(),
+      null,
       model.getOffsetAt(position),
       context.triggerKind === CompletionTriggerKind.Invoke
     );
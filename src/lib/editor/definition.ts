@@ -0,0 +1,40 @@
+import type { CancellationToken, editor as editorType, languages, Position } from "monaco-editor";
+import { editor, Uri } from "monaco-editor";
+
+import { definition, readFileText } from "../ipc";
+import { syncSource } from "./sync";
+
+export class TypstDefinitionProvider implements languages.DefinitionProvider {
+  async provideDefinition(
+    model: editorType.ITextModel,
+    position: Position,
+    token: CancellationToken
+  ): Promise<languages.Definition | null> {
+    await syncSource(model);
+    const location = await definition(model.uri.path, null, model.getOffsetAt(position));
+
+    // Package sources are not part of the project, so they cannot be opened
+    if (!location || location.package) return null;
+
+    // Other files are not open, so their positions are resolved from a temporary model
+    const target =
+      location.path === model.uri.path
+        ? model
+        : editor.createModel(await readFileText(location.path));
+    try {
+      const start = target.getPositionAt(location.range.start);
+      const end = target.getPositionAt(location.range.end);
+      return {
+        uri: Uri.file(location.path),
+        range: {
+          startLineNumber: start.lineNumber,
+          startColumn: start.column,
+          endLineNumber: end.lineNumber,
+          endColumn: end.column,
+        },
+      };
+    } finally {
+      if (target !== model) target.dispose();
+    }
+  }
+}
@@ -0,0 +1,52 @@
+import type { CancellationToken, editor, languages, Position } from "monaco-editor";
+
+import { bibliographyEntry, tooltip } from "../ipc";
+import type { TypstBibliographyEntry } from "../ipc";
+import { syncSource } from "./sync";
+
+// Matches `@key` and `cite(<key>)`
+const CITATION_PATTERN = /@([\w\-.:]+)|cite\(\s*<([\w\-.:]+)>/g;
+
+const citationKeyAt = (model: editor.ITextModel, position: Position): string | null => {
+  const line = model.getLineContent(position.lineNumber);
+  for (const match of line.matchAll(CITATION_PATTERN)) {
+    const start = (match.index ?? 0) + 1;
+    const end = start + match[0].length;
+    if (position.column >= start && position.column <= end) {
+      return match[1] ?? match[2];
+    }
+  }
+  return null;
+};
+
+const formatEntry = (entry: TypstBibliographyEntry): string => {
+  const details = [entry.authors.join(", "), entry.container, entry.year?.toString()].filter(
+    (detail) => !!detail
+  );
+  return [`**${entry.title ?? entry.key}**`, details.join(" · ")]
+    .filter((line) => !!line)
+    .join("\n\n");
+};
+
+export class TypstHoverProvider implements languages.HoverProvider {
+  async provideHover(
+    model: editor.ITextModel,
+    position: Position,
+    token: CancellationToken
+  ): Promise<languages.Hover | null> {
+    const key = citationKeyAt(model, position);
+    if (key) {
+      const entry = await bibliographyEntry(key);
+      if (entry) return { contents: [{ value: formatEntry(entry) }] };
+    }
+
+    await syncSource(model);
+    const res = await tooltip(model.uri.path, null, model.getOffsetAt(position));
+    if (!res) return null;
+
+    const value = res.kind === "code" ? "```typst\n" + res.value + "\n```" : res.value;
+    return {
+      contents: [{ value }],
+    };
+  }
+}
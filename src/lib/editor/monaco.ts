@@ -9,7 +9,18 @@ import typstConfig from "./lang/typst-config.json";
 import typstTm from "./lang/typst-tm.json";
 import theme from "./theme/theme.json";
 
+import {
+  CREATE_FILE_COMMAND,
+  createFileCommand,
+  TypstCodeActionProvider,
+} from "$lib/editor/actions";
 import { TypstCompletionProvider } from "$lib/editor/completion";
+import { TypstDefinitionProvider } from "$lib/editor/definition";
+import { TypstDocumentSymbolProvider, TypstFoldingRangeProvider } from "$lib/editor/structure";
+import { TypstHoverProvider } from "$lib/editor/hover";
+import { TypstRenameProvider } from "$lib/editor/rename";
+import { TypstSemanticTokensProvider } from "$lib/editor/semantic";
+import { semanticTokensLegend } from "$lib/ipc";
 
 type IMonarchLanguage = monaco.languages.IMonarchLanguage;
 
@@ -44,6 +55,22 @@ export const initMonaco = (async () => {
 
   // Register completion providers
   monaco.languages.registerCompletionItemProvider("typst", new TypstCompletionProvider());
+  monaco.languages.registerHoverProvider("typst", new TypstHoverProvider());
+  monaco.languages.registerRenameProvider("typst", new TypstRenameProvider());
+  monaco.languages.registerDefinitionProvider("typst", new TypstDefinitionProvider());
+  monaco.languages.registerFoldingRangeProvider("typst", new TypstFoldingRangeProvider());
+  monaco.languages.registerDocumentSymbolProvider("typst", new TypstDocumentSymbolProvider());
+  monaco.languages.registerCodeActionProvider("typst", new TypstCodeActionProvider(), {
+    providedCodeActionKinds: ["quickfix"],
+  });
+  monaco.editor.registerCommand(CREATE_FILE_COMMAND, createFileCommand);
+
+  // Semantic tokens take precedence over the TextMate grammar where they are provided
+  const legend = await semanticTokensLegend();
+  monaco.languages.registerDocumentSemanticTokensProvider(
+    "typst",
+    new TypstSemanticTokensProvider(legend)
+  );
 
   monaco.editor.defineTheme("dracula", theme as monaco.editor.IStandaloneThemeData);
   monaco.editor.setTheme("dracula");
@@ -0,0 +1,89 @@
+import type { CancellationToken, editor, languages, Position } from "monaco-editor";
+
+import type { TypstFileEdit } from "../ipc";
+import { applyEdit, rename } from "../ipc";
+import { syncSource } from "./sync";
+
+/**
+ * Writes the edits of other files once the model contains the edits of the current file.
+ * Monaco may reject the edits of the current file, e.g. if the model changed in the
+ * meantime, or the rename may be cancelled, which leaves the other files untouched
+ * instead of half-renamed.
+ */
+const applyAfterModel = (
+  model: editor.ITextModel,
+  expected: string,
+  others: TypstFileEdit[],
+  token: CancellationToken
+) => {
+  const dispose = () => {
+    listener.dispose();
+    cancellation.dispose();
+  };
+  const listener = model.onDidChangeContent(() => {
+    dispose();
+    if (model.getValue() === expected) {
+      applyEdit({ files: others });
+    }
+  });
+  const cancellation = token.onCancellationRequested(dispose);
+};
+
+export class TypstRenameProvider implements languages.RenameProvider {
+  async provideRenameEdits(
+    model: editor.ITextModel,
+    position: Position,
+    newName: string,
+    token: CancellationToken
+  ): Promise<languages.WorkspaceEdit & languages.Rejection> {
+    try {
+      await syncSource(model);
+      const edit = await rename(model.uri.path, null, model.getOffsetAt(position), newName);
+      if (token.isCancellationRequested) {
+        return { edits: [] };
+      }
+
+      // Other files are not open, so they are edited and saved by the backend
+      const others = edit.files.filter((file) => file.path !== model.uri.path);
+      const current = edit.files
+        .filter((file) => file.path === model.uri.path)
+        .flatMap((file) => file.edits);
+
+      if (others.length > 0) {
+        if (current.length > 0) {
+          // Edits are applied from the end so that earlier offsets stay valid
+          let expected = model.getValue();
+          const sorted = [...current].sort((a, b) => b.range.start - a.range.start);
+          for (const { range, text } of sorted) {
+            expected = expected.slice(0, range.start) + text + expected.slice(range.end);
+          }
+          applyAfterModel(model, expected, others, token);
+        } else {
+          await applyEdit({ files: others });
+        }
+      }
+
+      const versionId = model.getVersionId();
+      const edits = current.map(({ range, text }) => {
+        const start = model.getPositionAt(range.start);
+        const end = model.getPositionAt(range.end);
+        return {
+          resource: model.uri,
+          versionId,
+          textEdit: {
+            range: {
+              startLineNumber: start.lineNumber,
+              startColumn: start.column,
+              endLineNumber: end.lineNumber,
+              endColumn: end.column,
+            },
+            text,
+          },
+        };
+      });
+      return { edits };
+    } catch (e) {
+      return { edits: [], rejectReason: String(e) };
+    }
+  }
+}
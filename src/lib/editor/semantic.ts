@@ -0,0 +1,40 @@
+import type { CancellationToken, editor, languages } from "monaco-editor";
+
+import type { TypstSemanticTokensLegend } from "../ipc";
+import { semanticTokens } from "../ipc";
+import { syncSource } from "./sync";
+
+export class TypstSemanticTokensProvider implements languages.DocumentSemanticTokensProvider {
+  constructor(private readonly legend: TypstSemanticTokensLegend) {}
+
+  getLegend(): languages.SemanticTokensLegend {
+    return {
+      tokenTypes: this.legend.token_types,
+      tokenModifiers: this.legend.token_modifiers,
+    };
+  }
+
+  async provideDocumentSemanticTokens(
+    model: editor.ITextModel,
+    lastResultId: string | null,
+    token: CancellationToken
+  ): Promise<languages.SemanticTokens | languages.SemanticTokensEdits> {
+    // The backend keeps the source in sync, so only pending edits are sent
+    await syncSource(model);
+    const tokens = await semanticTokens(model.uri.path, null, lastResultId);
+
+    if (tokens.type === "delta") {
+      return {
+        resultId: tokens.result_id,
+        edits: tokens.edits.map(({ start, delete_count, data }) => ({
+          start,
+          deleteCount: delete_count,
+          data: new Uint32Array(data),
+        })),
+      };
+    }
+    return { resultId: tokens.result_id, data: new Uint32Array(tokens.data) };
+  }
+
+  releaseDocumentSemanticTokens(resultId: string | undefined) {}
+}
@@ -0,0 +1,65 @@
+import type { CancellationToken, editor, IRange } from "monaco-editor";
+import { languages } from "monaco-editor";
+
+import type { TypstDocumentSymbol } from "../ipc";
+import { documentSymbols, foldingRanges } from "../ipc";
+import { syncSource } from "./sync";
+
+export class TypstFoldingRangeProvider implements languages.FoldingRangeProvider {
+  async provideFoldingRanges(
+    model: editor.ITextModel,
+    context: languages.FoldingContext,
+    token: CancellationToken
+  ): Promise<languages.FoldingRange[]> {
+    // The backend keeps the source in sync, so only pending edits are sent
+    await syncSource(model);
+    const ranges = await foldingRanges(model.uri.path, null);
+
+    // Monaco uses one-based line numbers
+    return ranges.map((range) => ({
+      start: range.start_line + 1,
+      end: range.end_line + 1,
+      kind: range.kind === "comment" ? languages.FoldingRangeKind.Comment : undefined,
+    }));
+  }
+}
+
+const symbolKinds: Record<TypstDocumentSymbol["kind"], languages.SymbolKind> = {
+  heading: languages.SymbolKind.Namespace,
+  variable: languages.SymbolKind.Variable,
+  function: languages.SymbolKind.Function,
+  show_rule: languages.SymbolKind.Event,
+  label: languages.SymbolKind.Key,
+};
+
+export class TypstDocumentSymbolProvider implements languages.DocumentSymbolProvider {
+  async provideDocumentSymbols(
+    model: editor.ITextModel,
+    token: CancellationToken
+  ): Promise<languages.DocumentSymbol[]> {
+    await syncSource(model);
+    const symbols = await documentSymbols(model.uri.path, null);
+
+    const toRange = ({ start, end }: { start: number; end: number }): IRange => {
+      const startPosition = model.getPositionAt(start);
+      const endPosition = model.getPositionAt(end);
+      return {
+        startLineNumber: startPosition.lineNumber,
+        startColumn: startPosition.column,
+        endLineNumber: endPosition.lineNumber,
+        endColumn: endPosition.column,
+      };
+    };
+    const toSymbol = (symbol: TypstDocumentSymbol): languages.DocumentSymbol => ({
+      name: symbol.name,
+      detail: symbol.detail ?? "",
+      kind: symbolKinds[symbol.kind],
+      tags: [],
+      range: toRange(symbol.range),
+      selectionRange: toRange(symbol.selection_range),
+      children: symbol.children.map(toSymbol),
+    });
+
+    return symbols.map(toSymbol);
+  }
+}
@@ -0,0 +1,56 @@
+import type { editor } from "monaco-editor";
+
+import type { TypstSourceEdit } from "../ipc";
+import { compile, edit } from "../ipc";
+
+// Edits which have not been sent yet, by path. If there are no entries, the whole
+// content has to be sent, e.g. after the model has been loaded.
+const pending = new Map<string, TypstSourceEdit[]>();
+
+// Requests are chained, so that edits arrive in order
+let queue: Promise<unknown> = Promise.resolve();
+
+export const resetSource = (model: editor.ITextModel) => {
+  pending.delete(model.uri.path);
+};
+
+export const queueEdits = (model: editor.ITextModel, event: editor.IModelContentChangedEvent) => {
+  const edits = pending.get(model.uri.path);
+  if (!edits) return;
+
+  // Changes are ordered from the end to the beginning, so they can be applied in sequence
+  for (const { rangeOffset, rangeLength, text } of event.changes) {
+    edits.push({ range: { start: rangeOffset, end: rangeOffset + rangeLength }, text });
+  }
+};
+
+// A failed request must not skip the requests after it, and the backend may have
+// missed the edits, so the next sync of the source sends its whole content
+const enqueue = (path: string, request: () => Promise<unknown>): Promise<unknown> =>
+  (queue = queue
+    .catch(() => {})
+    .then(request)
+    .catch((e) => {
+      pending.delete(path);
+      throw e;
+    }));
+
+/**
+ * Sends the pending edits of the model, or its whole content if the backend may be
+ * out of sync. This queues a compilation, unless there is nothing to send.
+ */
+export const syncSource = (model: editor.ITextModel): Promise<unknown> => {
+  const path = model.uri.path;
+  const edits = pending.get(path);
+  pending.set(path, []);
+
+  if (!edits) {
+    const content = model.getValue();
+    return enqueue(path, () => compile(path, content));
+  }
+  if (edits.length > 0) {
+    return enqueue(path, () => edit(path, edits));
+  }
+  // Failures have already been handled by the request which failed
+  return queue.catch(() => {});
+};
@@ -13,6 +13,11 @@ export interface FSRefreshEvent {
   path: string;
 }
 
+export interface FSReloadEvent {
+  // Path of the source, relative to the project root
+  path: string;
+}
+
 export interface ProjectChangeEvent {
   project: Project | null;
 }
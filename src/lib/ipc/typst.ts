@@ -1,12 +1,21 @@
 import { invoke } from "@tauri-apps/api";
 
 export interface TypstCompileEvent {
+  revision: number;
   document: TypstDocument | null;
   diagnostics: TypstSourceDiagnostic[] | null;
+  detached_diagnostics: TypstDiagnostic[] | null;
 }
 
 export interface TypstDocument {
-  pages: number;
+  pages: TypstPage[];
+  hash: string;
+  changed_pages: number[];
+}
+
+export interface TypstPage {
+  number: number;
+  label: string | null;
   hash: string;
   width: number;
   height: number;
@@ -14,13 +23,18 @@ export interface TypstDocument {
 
 export type TypstDiagnosticSeverity = "error" | "warning";
 
-export interface TypstSourceDiagnostic {
-  range: { start: number; end: number };
+export interface TypstDiagnostic {
   severity: TypstDiagnosticSeverity;
   message: string;
   hints: string[];
 }
 
+export interface TypstSourceDiagnostic extends TypstDiagnostic {
+  path: string;
+  package: string | null;
+  range: { start: number; end: number };
+}
+
 export interface TypstRenderResponse {
   image: string;
   width: number;
@@ -28,6 +42,211 @@ export interface TypstRenderResponse {
   nonce: number;
 }
 
+export interface TypstPagePosition {
+  page: number;
+  x: number;
+  y: number;
+}
+
+export interface TypstLocation {
+  path: string;
+  package: string | null;
+  range: { start: number; end: number };
+}
+
+export interface TypstOutlineEvent {
+  revision: number;
+  items: TypstOutlineItem[];
+}
+
+export type TypstOutlineKind = "heading" | "figure" | "table" | "equation";
+
+export interface TypstOutlineItem {
+  kind: TypstOutlineKind;
+  title: string;
+  level: number;
+  numbering: string | null;
+  label: string | null;
+  position: TypstPagePosition;
+  location: TypstLocation | null;
+  children: TypstOutlineItem[];
+}
+
+export interface TypstLabel {
+  name: string;
+  definitions: TypstLocation[];
+  references: TypstLocation[];
+  duplicate: boolean;
+  unused: boolean;
+  broken: boolean;
+}
+
+export interface TypstBibliographyEntry {
+  key: string;
+  title: string | null;
+  authors: string[];
+  year: number | null;
+  container: string | null;
+  path: string;
+}
+
+export interface TypstTextCount {
+  words: number;
+  characters: number;
+  characters_with_spaces: number;
+}
+
+export interface TypstTextStatistics {
+  body: TypstTextCount;
+  headings: TypstTextCount;
+  captions: TypstTextCount;
+  bibliography: TypstTextCount;
+}
+
+export interface TypstSectionStatistics {
+  title: string | null;
+  level: number;
+  location: TypstLocation | null;
+  statistics: TypstTextStatistics;
+}
+
+export interface TypstFileStatistics {
+  path: string;
+  statistics: TypstTextStatistics;
+}
+
+export interface TypstStatistics {
+  document: TypstTextStatistics;
+  pages: TypstTextStatistics[];
+  sections: TypstSectionStatistics[];
+  files: TypstFileStatistics[];
+}
+
+export interface TypstStatisticsEvent {
+  revision: number;
+  statistics: TypstStatistics;
+}
+
+export interface TypstSymbol {
+  path: string;
+  module: "sym" | "emoji";
+  name: string;
+  variant: string;
+  character: string;
+  codepoint: number;
+}
+
+export type TypstDocsKind = "function" | "type" | "module" | "value";
+
+export interface TypstParam {
+  name: string;
+  docs: string;
+  types: string[];
+  default: string | null;
+  positional: boolean;
+  named: boolean;
+  variadic: boolean;
+  required: boolean;
+  settable: boolean;
+}
+
+export interface TypstDocsMember {
+  name: string;
+  kind: TypstDocsKind;
+}
+
+export interface TypstDocs {
+  path: string;
+  kind: TypstDocsKind;
+  title: string;
+  docs: string;
+  params: TypstParam[];
+  returns: string[];
+  members: TypstDocsMember[];
+}
+
+export type TypstSemanticTokens =
+  | { type: "full"; result_id: string; data: number[] }
+  | { type: "delta"; result_id: string; edits: TypstSemanticTokensEdit[] };
+
+export interface TypstSemanticTokensEdit {
+  start: number;
+  delete_count: number;
+  data: number[];
+}
+
+export interface TypstSemanticTokensLegend {
+  token_types: string[];
+  token_modifiers: string[];
+}
+
+export interface TypstFoldingRange {
+  // Zero-based and inclusive
+  start_line: number;
+  end_line: number;
+  kind: "comment" | null;
+}
+
+export type TypstDocumentSymbolKind = "heading" | "variable" | "function" | "show_rule" | "label";
+
+export interface TypstDocumentSymbol {
+  name: string;
+  detail: string | null;
+  kind: TypstDocumentSymbolKind;
+  range: { start: number; end: number };
+  selection_range: { start: number; end: number };
+  children: TypstDocumentSymbol[];
+}
+
+export interface TypstTextEdit {
+  range: { start: number; end: number };
+  text: string;
+}
+
+export interface TypstSourceEdit {
+  // UTF-16 code units, as reported by the editor
+  range: { start: number; end: number };
+  text: string;
+}
+
+export interface TypstFileEdit {
+  path: string;
+  edits: TypstTextEdit[];
+}
+
+export interface TypstWorkspaceEdit {
+  files: TypstFileEdit[];
+}
+
+export interface TypstCodeAction {
+  title: string;
+  // The diagnostic which is fixed
+  message: string;
+  range: { start: number; end: number };
+  create_file: string | null;
+  edit: TypstWorkspaceEdit;
+}
+
+export interface TypstPngExportOptions {
+  // One-based and inclusive, defaulting to all pages
+  first_page: number | null;
+  last_page: number | null;
+  dpi: number;
+  // `{name}` is replaced by the name of the main source and `{page}` by the page number
+  pattern: string;
+}
+
+export interface TypstSvgExportOptions {
+  // One-based, or null to export each page to its own file
+  page: number | null;
+  pattern: string;
+}
+
+export type TypstJump =
+  | { type: "source"; path: string; package: string | null; offset: number }
+  | { type: "url"; url: string }
+  | ({ type: "position" } & TypstPagePosition);
+
 export enum TypstCompletionKind {
   Syntax = 1,
   Function = 2,
@@ -49,16 +268,109 @@ export interface TypstCompleteResponse {
   completions: TypstCompletion[];
 }
 
-export const compile = (path: string, content: string): Promise<TypstRenderResponse> =>
-  invoke<TypstRenderResponse>("typst_compile", { path, content });
+export type TypstTooltip = { kind: "text"; value: string } | { kind: "code"; value: string };
+
+export const compile = (path: string, content: string): Promise<number> =>
+  invoke<number>("typst_compile", { path, content });
+
+export const edit = (path: string, edits: TypstSourceEdit[]): Promise<number> =>
+  invoke<number>("typst_edit", { path, edits });
 
 export const render = (page: number, scale: number, nonce: number): Promise<TypstRenderResponse> =>
   invoke<TypstRenderResponse>("typst_render", { page, scale, nonce });
 
+export const exportPng = (directory: string, options: TypstPngExportOptions): Promise<string[]> =>
+  invoke<string[]>("typst_export_png", { directory, options });
+
+export const exportSvg = (directory: string, options: TypstSvgExportOptions): Promise<string[]> =>
+  invoke<string[]>("typst_export_svg", { directory, options });
+
 export const autocomplete = (
   path: string,
-  content: string,
+  content: string | null,
   offset: number,
   explicit: boolean
 ): Promise<TypstCompleteResponse> =>
   invoke<TypstCompleteResponse>("typst_autocomplete", { path, content, offset, explicit });
+
+export const jumpFromClick = (page: number, x: number, y: number): Promise<TypstJump | null> =>
+  invoke<TypstJump | null>("typst_jump_from_click", { page, x, y });
+
+export const jumpFromCursor = (path: string, offset: number): Promise<TypstPagePosition | null> =>
+  invoke<TypstPagePosition | null>("typst_jump_from_cursor", { path, offset });
+
+export const tooltip = (
+  path: string,
+  content: string | null,
+  offset: number
+): Promise<TypstTooltip | null> =>
+  invoke<TypstTooltip | null>("typst_tooltip", { path, content, offset });
+
+export const definition = (
+  path: string,
+  content: string | null,
+  offset: number
+): Promise<TypstLocation | null> =>
+  invoke<TypstLocation | null>("typst_definition", { path, content, offset });
+
+export const outline = (): Promise<TypstOutlineItem[]> =>
+  invoke<TypstOutlineItem[]>("typst_outline");
+
+export const labels = (): Promise<TypstLabel[]> => invoke<TypstLabel[]>("typst_labels");
+
+export const statistics = (): Promise<TypstStatistics> =>
+  invoke<TypstStatistics>("typst_statistics");
+
+export const symbols = (query: string | null): Promise<TypstSymbol[]> =>
+  invoke<TypstSymbol[]>("typst_symbols", { query });
+
+export const symbolInsertion = (
+  path: string,
+  content: string | null,
+  offset: number,
+  symbol: string
+): Promise<string> =>
+  invoke<string>("typst_symbol_insertion", { path, content, offset, symbol });
+
+export const docs = (name: string | null): Promise<TypstDocs | null> =>
+  invoke<TypstDocs | null>("typst_docs", { name });
+
+export const semanticTokensLegend = (): Promise<TypstSemanticTokensLegend> =>
+  invoke<TypstSemanticTokensLegend>("typst_semantic_tokens_legend");
+
+export const semanticTokens = (
+  path: string,
+  content: string | null,
+  previousResultId: string | null
+): Promise<TypstSemanticTokens> =>
+  invoke<TypstSemanticTokens>("typst_semantic_tokens", { path, content, previousResultId });
+
+export const foldingRanges = (path: string, content: string | null): Promise<TypstFoldingRange[]> =>
+  invoke<TypstFoldingRange[]>("typst_folding_ranges", { path, content });
+
+export const documentSymbols = (
+  path: string,
+  content: string | null
+): Promise<TypstDocumentSymbol[]> =>
+  invoke<TypstDocumentSymbol[]>("typst_document_symbols", { path, content });
+
+export const codeActions = (
+  path: string,
+  content: string | null,
+  range: { start: number; end: number }
+): Promise<TypstCodeAction[]> =>
+  invoke<TypstCodeAction[]>("typst_code_actions", { path, content, range });
+
+export const bibliographyEntry = (key: string): Promise<TypstBibliographyEntry | null> =>
+  invoke<TypstBibliographyEntry | null>("typst_bibliography_entry", { key });
+
+export const rename = (
+  path: string,
+  content: string | null,
+  offset: number,
+  newName: string
+): Promise<TypstWorkspaceEdit> =>
+  invoke<TypstWorkspaceEdit>("typst_rename", { path, content, offset, newName });
+
+export const applyEdit = (edit: TypstWorkspaceEdit): Promise<void> =>
+  invoke<void>("typst_apply_edit", { edit });
@@ -1,5 +1,7 @@
 import { writable } from "svelte/store";
 
+import type { TypstPngExportOptions, TypstSvgExportOptions } from "./ipc";
+
 export interface Project {
   root: string;
 }
@@ -22,7 +24,17 @@ export interface InputModal extends BaseModal {
   callback: (content: string | null) => void;
 }
 
-export type Modal = InputModal;
+export interface ExportPngModal extends BaseModal {
+  type: "export_png";
+  callback: (options: TypstPngExportOptions | null) => void;
+}
+
+export interface ExportSvgModal extends BaseModal {
+  type: "export_svg";
+  callback: (options: TypstSvgExportOptions | null) => void;
+}
+
+export type Modal = InputModal | ExportPngModal | ExportSvgModal;
 
 export enum PreviewState {
   Idle,